@@ -0,0 +1,262 @@
+use std::{error::Error, fmt, time::Duration};
+
+use alloy_primitives::{Address, B256};
+use futures::{stream, Stream, StreamExt};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Deserializer};
+use tokio::time;
+
+// Path of the beacon node event stream, subscribed to head and payload attributes
+const EVENTS_PATH: &str = "/eth/v1/events?topics=head,payload_attributes";
+
+// Timeout for establishing the event stream; the stream itself stays open
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Default time to wait for the next chunk before treating the stream as stalled.
+// Beacon nodes emit a head event every slot, so several slots of silence mean the
+// connection is dead even if it was never closed.
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+// Beacon API encodes integers as decimal strings
+fn deserialize_u64_from_string<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    s.parse().map_err(serde::de::Error::custom)
+}
+
+// Errors returned while reading the beacon node event stream
+#[derive(Debug)]
+pub enum BeaconEventError {
+    // The stream could not be opened or was interrupted
+    Http(reqwest::Error),
+    // The beacon node answered with a non-success status code
+    Status {
+        status: StatusCode,
+        body: String,
+    },
+    // An event payload does not match the expected format
+    Decode {
+        event: String,
+        source: serde_json::Error,
+    },
+    // No data arrived within the idle timeout
+    Idle(Duration),
+}
+
+impl fmt::Display for BeaconEventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BeaconEventError::Http(err) => write!(f, "beacon event stream failed: {}", err),
+            BeaconEventError::Status { status, body } => {
+                write!(f, "beacon node returned {}: {}", status, body)
+            }
+            BeaconEventError::Decode { event, source } => {
+                write!(f, "failed to decode {} event: {}", event, source)
+            }
+            BeaconEventError::Idle(timeout) => {
+                write!(f, "no data from the beacon node for {:?}", timeout)
+            }
+        }
+    }
+}
+
+impl Error for BeaconEventError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BeaconEventError::Http(err) => Some(err),
+            BeaconEventError::Status { .. } | BeaconEventError::Idle(_) => None,
+            BeaconEventError::Decode { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<reqwest::Error> for BeaconEventError {
+    fn from(err: reqwest::Error) -> Self {
+        BeaconEventError::Http(err)
+    }
+}
+
+// A new head block was imported
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HeadEvent {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub slot: u64,
+    pub block: String,
+    pub state: String,
+    #[serde(default)]
+    pub epoch_transition: bool,
+}
+
+// Attributes of the execution payload the next proposer will request
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PayloadAttributes {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub timestamp: u64,
+    pub prev_randao: String,
+    pub suggested_fee_recipient: Address,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PayloadAttributesData {
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub proposer_index: u64,
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub proposal_slot: u64,
+    #[serde(deserialize_with = "deserialize_u64_from_string")]
+    pub parent_block_number: u64,
+    pub parent_block_root: String,
+    // Execution block hash every bid for `proposal_slot` must build on
+    pub parent_block_hash: B256,
+    pub payload_attributes: PayloadAttributes,
+}
+
+// The beacon node is preparing a payload for an upcoming proposal
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PayloadAttributesEvent {
+    pub version: String,
+    pub data: PayloadAttributesData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BeaconEvent {
+    Head(HeadEvent),
+    PayloadAttributes(PayloadAttributesEvent),
+}
+
+impl BeaconEvent {
+    // Parses one server-sent event; topics this crate does not use yield `None`
+    fn parse(event: &str, data: &str) -> Option<Result<Self, BeaconEventError>> {
+        let decode_error = |source| BeaconEventError::Decode {
+            event: event.to_string(),
+            source,
+        };
+        match event {
+            "head" => Some(
+                serde_json::from_str(data)
+                    .map(BeaconEvent::Head)
+                    .map_err(decode_error),
+            ),
+            "payload_attributes" => Some(
+                serde_json::from_str(data)
+                    .map(BeaconEvent::PayloadAttributes)
+                    .map_err(decode_error),
+            ),
+            _ => None,
+        }
+    }
+}
+
+// Accumulates raw bytes from the event stream and splits them into events. Only
+// complete lines are decoded, so a character or a CRLF split across chunks survives.
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    // Returns the (event, data) pairs completed by `chunk`
+    fn push(&mut self, chunk: &[u8]) -> Vec<(String, String)> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\n').trim_end_matches('\r');
+
+            if line.is_empty() {
+                // A blank line dispatches the event
+                let event = self.event.take();
+                if !self.data.is_empty() {
+                    let data = std::mem::take(&mut self.data).join("\n");
+                    events.push((event.unwrap_or_else(|| "message".to_string()), data));
+                }
+            } else if let Some(value) = line.strip_prefix("event:") {
+                self.event = Some(value.trim().to_string());
+            } else if let Some(value) = line.strip_prefix("data:") {
+                self.data
+                    .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+            }
+        }
+        events
+    }
+}
+
+// Reads head and payload attributes events from a beacon node
+pub struct BeaconEventSource {
+    pub beacon_url: String,
+    pub idle_timeout: Duration,
+    http: Client,
+}
+
+impl BeaconEventSource {
+    // Fails only if the HTTP client cannot be set up
+    pub fn new(beacon_url: String) -> Result<Self, BeaconEventError> {
+        let http = Client::builder().connect_timeout(CONNECT_TIMEOUT).build()?;
+
+        Ok(Self {
+            beacon_url: beacon_url.trim_end_matches('/').to_string(),
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            http,
+        })
+    }
+
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    // Opens the event stream. The returned stream ends when the beacon node closes
+    // the connection, or after an `Idle` error when no data arrives within the idle
+    // timeout; callers reconnect by subscribing again.
+    pub async fn subscribe(
+        &self,
+    ) -> Result<impl Stream<Item = Result<BeaconEvent, BeaconEventError>>, BeaconEventError> {
+        let url = format!("{}{}", self.beacon_url, EVENTS_PATH);
+        let response = self
+            .http
+            .get(&url)
+            .header("accept", "text/event-stream")
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(BeaconEventError::Status { status, body });
+        }
+
+        let bytes = response.bytes_stream();
+        let idle_timeout = self.idle_timeout;
+        let events = stream::unfold(
+            Some((bytes, SseParser::default())),
+            move |state| async move {
+                let (mut bytes, mut parser) = state?;
+                let chunk = match time::timeout(idle_timeout, bytes.next()).await {
+                    Ok(chunk) => chunk?,
+                    // A stalled stream is reported once, then ends
+                    Err(_) => {
+                        let idle = Err(BeaconEventError::Idle(idle_timeout));
+                        return Some((stream::iter(vec![idle]), None));
+                    }
+                };
+                let events: Vec<Result<BeaconEvent, BeaconEventError>> = match chunk {
+                    Ok(chunk) => parser
+                        .push(&chunk)
+                        .iter()
+                        .filter_map(|(event, data)| BeaconEvent::parse(event, data))
+                        .collect(),
+                    Err(err) => vec![Err(err.into())],
+                };
+                Some((stream::iter(events), Some((bytes, parser))))
+            },
+        )
+        .flatten();
+
+        Ok(Box::pin(events))
+    }
+}
@@ -1,92 +1,805 @@
 use std::{
     cmp::Reverse,
-    collections::{BinaryHeap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    ops::RangeInclusive,
+    pin::Pin,
     sync::Arc,
+    task::{Context, Poll},
 };
 
+use alloy_primitives::U256;
+use serde::Deserialize;
+
 use tokio::sync::{
-    mpsc::{self, Receiver, Sender},
+    broadcast::{self, error::RecvError, error::TryRecvError},
     RwLock,
 };
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    Stream,
+};
+
+use crate::{
+    relay_client::RelayError,
+    relay_config::RelayKind,
+    slot_clock::SlotClock,
+    types::{
+        BidDetails, BidEvent, BidKey, BidResponse, BidTrace, BidValidationError, BlsPublicKey,
+        MalformedBid, RelayBid,
+    },
+};
+
+// Number of slots kept by default, one epoch
+pub const DEFAULT_RETAINED_SLOTS: u64 = 32;
+
+// Number of notifications buffered per subscription before a slow subscriber lags
+pub const SUBSCRIPTION_CAPACITY: usize = 1024;
+
+// Number of rejected bids, and separately of malformed entries, kept per relay;
+// older ones are dropped first
+pub const QUARANTINE_CAPACITY: usize = 100;
+
+// Without a slot clock, bids more than this many slots past the newest slot
+// accepted so far are rejected, one day of slots. A bogus far-future slot would
+// otherwise move the retention window and prune every retained slot.
+pub const MAX_SLOTS_AHEAD: u64 = 7200;
+
+// Orders bids by value; among equal values the bid seen first ranks higher
+type BidRank = (U256, Reverse<u64>);
+
+// How to reconcile relays that report differing metadata for the same `BidKey`.
+// Receive timestamps are per relay and never count as a conflict.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MergePolicy {
+    // Keep the first report and only record the conflicting relay
+    #[default]
+    KeepFirst,
+    // Replace the stored bid when a relay reports a higher value
+    KeepHighestValue,
+    // Replace the stored bid when a relay reports a trace that differs from its own
+    // previous report, so relays that disagree do not flip the bid on every poll
+    KeepLatest,
+}
+
+// Outcome of adding a relay's bid to a slot
+#[derive(Debug, PartialEq, Eq)]
+enum Insertion {
+    // The block was not known from any relay
+    NewBid,
+    // The block was known and the merge policy replaced the stored bid
+    Replaced,
+    // The block was already known, but not from this relay
+    NewRelay,
+    // This relay already reported the block
+    Known,
+}
+
+// True when two reports describe the same block with the same metadata
+fn same_metadata(a: &BidTrace, b: &BidTrace) -> bool {
+    a.parent_hash == b.parent_hash
+        && a.proposer_pubkey == b.proposer_pubkey
+        && a.proposer_fee_recipient == b.proposer_fee_recipient
+        && a.gas_limit == b.gas_limit
+        && a.gas_used == b.gas_used
+        && a.value == b.value
+        && a.block_number == b.block_number
+        && a.num_tx == b.num_tx
+}
+
+// A bid record together with its position in the value index and the latest
+// trace each relay reported for it
+struct Entry {
+    rank: BidRank,
+    record: RelayBid,
+    reports: HashMap<String, BidTrace>,
+}
+
+// All bids received for a single slot, one record per `BidKey`
+#[derive(Default)]
+struct SlotBids {
+    bids: HashMap<BidKey, Entry>,
+    by_value: BTreeMap<BidRank, BidKey>,
+    next_seq: u64,
+}
+
+impl SlotBids {
+    fn highest(&self) -> Option<&RelayBid> {
+        self.descending().next()
+    }
+
+    // Bids from highest to lowest value
+    fn descending(&self) -> impl Iterator<Item = &RelayBid> {
+        self.by_value
+            .values()
+            .rev()
+            .map(|key| &self.bids[key].record)
+    }
+
+    fn get(&self, key: &BidKey) -> Option<&RelayBid> {
+        self.bids.get(key).map(|entry| &entry.record)
+    }
+
+    fn insert(
+        &mut self,
+        relay_url: &str,
+        bid: BidTrace,
+        details: BidDetails,
+        merge_policy: MergePolicy,
+    ) -> Insertion {
+        let key = bid.key();
+        let Some(entry) = self.bids.get_mut(&key) else {
+            let rank = (bid.value, Reverse(self.next_seq));
+            self.next_seq += 1;
+            self.by_value.insert(rank, key.clone());
+            let reports = HashMap::from([(relay_url.to_string(), bid.clone())]);
+            let mut record = RelayBid::new(relay_url, bid);
+            record.details.insert(relay_url.to_string(), details);
+            self.bids.insert(
+                key,
+                Entry {
+                    rank,
+                    record,
+                    reports,
+                },
+            );
+            return Insertion::NewBid;
+        };
+
+        entry.record.details.insert(relay_url.to_string(), details);
+        let report_changed = entry
+            .reports
+            .insert(relay_url.to_string(), bid.clone())
+            .is_none_or(|previous| !same_metadata(&previous, &bid));
+        let new_relay = entry
+            .record
+            .record_relay(relay_url, bid.timestamp_ms.saturating_to::<u64>());
+        if same_metadata(&entry.record.bid, &bid) {
+            return if new_relay {
+                Insertion::NewRelay
+            } else {
+                Insertion::Known
+            };
+        }
+
+        entry
+            .record
+            .conflicting_relays
+            .insert(relay_url.to_string());
+        let replace = match merge_policy {
+            MergePolicy::KeepFirst => false,
+            MergePolicy::KeepHighestValue => bid.value > entry.record.bid.value,
+            MergePolicy::KeepLatest => report_changed,
+        };
+        if !replace {
+            return if new_relay {
+                Insertion::NewRelay
+            } else {
+                Insertion::Known
+            };
+        }
+
+        // Re-rank under the new value, keeping the original arrival order
+        self.by_value.remove(&entry.rank);
+        entry.rank.0 = bid.value;
+        self.by_value.insert(entry.rank, key);
+        entry.record.bid = bid;
+        Insertion::Replaced
+    }
+}
+
+// Selects which events a bid stream yields. An unset filter matches everything.
+#[derive(Debug, Clone, Default)]
+pub struct BidFilter {
+    slots: Option<RangeInclusive<u64>>,
+    builders: Option<HashSet<BlsPublicKey>>,
+    relays: Option<HashSet<String>>,
+    min_value: Option<U256>,
+    new_top_only: bool,
+    new_bids_only: bool,
+}
+
+impl BidFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn slots(mut self, slots: RangeInclusive<u64>) -> Self {
+        self.slots = Some(slots);
+        self
+    }
+
+    pub fn builders<I>(mut self, builder_pubkeys: I) -> Self
+    where
+        I: IntoIterator<Item = BlsPublicKey>,
+    {
+        self.builders = Some(builder_pubkeys.into_iter().collect());
+        self
+    }
+
+    pub fn relays<I, S>(mut self, relay_urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.relays = Some(relay_urls.into_iter().map(Into::into).collect());
+        self
+    }
 
-use crate::types::BidTrace;
+    pub fn min_value(mut self, min_value: U256) -> Self {
+        self.min_value = Some(min_value);
+        self
+    }
+
+    // Only yield bids that became the top bid of their slot
+    pub fn new_top_only(mut self) -> Self {
+        self.new_top_only = true;
+        self
+    }
+
+    // Only yield newly seen bids, whether or not they became the top bid
+    pub fn new_bids_only(mut self) -> Self {
+        self.new_bids_only = true;
+        self
+    }
+
+    // An event without the attribute a filter inspects (e.g. the relay of a
+    // `SlotClosed` event) does not match that filter.
+    pub fn matches(&self, event: &BidEvent) -> bool {
+        if self.new_top_only && !matches!(event, BidEvent::NewTopBid { .. }) {
+            return false;
+        }
+        if self.new_bids_only
+            && !matches!(event, BidEvent::NewBid { .. } | BidEvent::NewTopBid { .. })
+        {
+            return false;
+        }
+        if let Some(slots) = &self.slots {
+            if !event.slot().is_some_and(|slot| slots.contains(&slot)) {
+                return false;
+            }
+        }
+        if let Some(builders) = &self.builders {
+            if !event
+                .bid()
+                .is_some_and(|bid| builders.contains(&bid.builder_pubkey))
+            {
+                return false;
+            }
+        }
+        if let Some(relays) = &self.relays {
+            if !event.relay().is_some_and(|relay| relays.contains(relay)) {
+                return false;
+            }
+        }
+        self.min_value
+            .is_none_or(|min_value| event.bid().is_some_and(|bid| bid.value >= min_value))
+    }
+}
 
-// Manages (sort, organize) all bids given by relays
+// A bid a relay reported that failed validation and was kept out of the auction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuarantinedBid {
+    pub bid: BidTrace,
+    pub error: BidValidationError,
+}
+
+// What a relay reported that was kept out of the auction, oldest first
+#[derive(Debug, Default)]
+struct RelayQuarantine {
+    bids: VecDeque<QuarantinedBid>,
+    malformed: VecDeque<MalformedBid>,
+}
+
+// Manages (sort, organize) all bids given by relays, partitioned by slot. Bids that
+// fail validation are quarantined per relay instead.
 #[derive(Clone)]
 pub struct BidManager {
-    highest_bid: Arc<RwLock<Option<BidTrace>>>,
-    all_bids: Arc<RwLock<BinaryHeap<Reverse<BidTrace>>>>,
-    unique_bids: Arc<RwLock<HashSet<BidTrace>>>,
-    top_bid_subscribers: Arc<RwLock<Vec<Sender<BidTrace>>>>,
-    new_bid_subscribers: Arc<RwLock<Vec<Sender<BidTrace>>>>,
+    slots: Arc<RwLock<BTreeMap<u64, SlotBids>>>,
+    quarantine: Arc<RwLock<HashMap<String, RelayQuarantine>>>,
+    retained_slots: u64,
+    merge_policy: MergePolicy,
+    slot_clock: Option<SlotClock>,
+    events: broadcast::Sender<BidEvent>,
+}
+
+impl Default for BidManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BidManager {
     pub fn new() -> Self {
+        Self::with_retention(DEFAULT_RETAINED_SLOTS)
+    }
+
+    // Keeps bids for the newest `retained_slots` slots and prunes anything older
+    pub fn with_retention(retained_slots: u64) -> Self {
         Self {
-            highest_bid: Arc::new(RwLock::new(None)),
-            all_bids: Arc::new(RwLock::new(BinaryHeap::new())),
-            unique_bids: Arc::new(RwLock::new(HashSet::new())),
-            top_bid_subscribers: Arc::new(RwLock::new(Vec::new())),
-            new_bid_subscribers: Arc::new(RwLock::new(Vec::new())),
-        }
-    }
-
-    pub(crate) async fn add_bids(&self, new_bids: Vec<BidTrace>) {
-        let mut all_bids_guard = self.all_bids.write().await;
-        let mut highest_bid_guard = self.highest_bid.write().await;
-        let mut unique_bids_guard = self.unique_bids.write().await;
-        let top_bid_subscribers_guard = self.top_bid_subscribers.read().await;
-        let new_bid_subscribers_guard: tokio::sync::RwLockReadGuard<'_, Vec<Sender<BidTrace>>> =
-            self.new_bid_subscribers.read().await;
-
-        for bid in new_bids {
-            if unique_bids_guard.insert(bid.clone()) {
-                all_bids_guard.push(Reverse(bid.clone()));
-                for subscriber in &*new_bid_subscribers_guard {
-                    let _ = subscriber.send(bid.clone()).await; // Ignore errors
+            slots: Arc::new(RwLock::new(BTreeMap::new())),
+            quarantine: Arc::new(RwLock::new(HashMap::new())),
+            retained_slots: retained_slots.max(1),
+            merge_policy: MergePolicy::default(),
+            slot_clock: None,
+            events: broadcast::channel(SUBSCRIPTION_CAPACITY).0,
+        }
+    }
+
+    // Sets how conflicting reports for the same `BidKey` are reconciled
+    pub fn with_merge_policy(mut self, merge_policy: MergePolicy) -> Self {
+        self.merge_policy = merge_policy;
+        self
+    }
+
+    // Also rejects bids submitted more than a slot away from their slot on `clock`,
+    // and bids for slots after the next one. The retention window then never moves
+    // past the clock's current slot.
+    pub fn with_slot_clock(mut self, clock: SlotClock) -> Self {
+        self.slot_clock = Some(clock);
+        self
+    }
+
+    // Adds the bids `relay_url` reported. A bid whose `BidKey` is already known is
+    // merged into the existing record according to the merge policy.
+    pub async fn add_bids(&self, relay_url: &str, new_bids: Vec<BidTrace>) {
+        let new_bids = new_bids
+            .into_iter()
+            .map(|bid| (bid, BidDetails::Standard))
+            .collect();
+        self.add_bids_with_details(relay_url, new_bids).await
+    }
+
+    // Adds the bids of a relay response, tagged with the relay's kind-specific details.
+    // Entries the relay reported that could not be decoded are quarantined.
+    pub async fn add_bid_response(&self, response: BidResponse) {
+        let new_bids = response
+            .bid_traces
+            .iter()
+            .map(|bid| (bid.clone(), response.details_for(bid)))
+            .collect();
+        self.quarantine_malformed(&response.relay_url, response.malformed)
+            .await;
+        self.add_bids_with_details(&response.relay_url, new_bids)
+            .await
+    }
+
+    async fn add_bids_with_details(&self, relay_url: &str, new_bids: Vec<(BidTrace, BidDetails)>) {
+        let mut accepted = Vec::with_capacity(new_bids.len());
+        let mut rejected = Vec::new();
+        for (bid, details) in new_bids {
+            match self.validate(&bid) {
+                Ok(()) => accepted.push((bid, details)),
+                Err(error) => rejected.push(QuarantinedBid { bid, error }),
+            }
+        }
+        self.quarantine_bids(relay_url, rejected).await;
+
+        let mut slots_guard = self.slots.write().await;
+        let mut too_far_ahead = Vec::new();
+
+        for (bid, details) in accepted {
+            let slot = bid.slot.saturating_to::<u64>();
+            if self.slot_clock.is_none() {
+                if let Some(&newest) = slots_guard.keys().next_back() {
+                    if slot > newest.saturating_add(MAX_SLOTS_AHEAD) {
+                        too_far_ahead.push(QuarantinedBid {
+                            bid,
+                            error: BidValidationError::SlotTooFarAhead {
+                                slot: U256::from(slot),
+                                reference_slot: newest,
+                            },
+                        });
+                        continue;
+                    }
                 }
-                match highest_bid_guard.as_ref() {
-                    Some(highest) if bid.value <= highest.value => (),
-                    _ => {
-                        *highest_bid_guard = Some(bid.clone());
-                        for subscriber in &*top_bid_subscribers_guard {
-                            let _ = subscriber.send(bid.clone()).await; // Ignore errors
-                        }
+            }
+            if slot < self.oldest_retained_slot(&slots_guard) {
+                continue;
+            }
+
+            let slot_bids = slots_guard.entry(slot).or_default();
+            let previous = slot_bids.highest().map(|highest| highest.bid.clone());
+            let is_new_top = previous
+                .as_ref()
+                .is_none_or(|previous| bid.value > previous.value);
+            let key = bid.key();
+            let relay = relay_url.to_string();
+            let event = match slot_bids.insert(relay_url, bid.clone(), details, self.merge_policy) {
+                Insertion::NewBid | Insertion::Replaced if is_new_top => {
+                    let delta = previous
+                        .as_ref()
+                        .map_or(bid.value, |previous| bid.value - previous.value);
+                    BidEvent::NewTopBid {
+                        relay,
+                        bid,
+                        previous: previous.map(Box::new),
+                        delta,
                     }
                 }
+                Insertion::NewBid => BidEvent::NewBid { relay, bid },
+                Insertion::NewRelay => {
+                    let record = slot_bids.get(&key).expect("bid was just merged");
+                    BidEvent::BidSeenOnAdditionalRelay {
+                        relay,
+                        bid: record.bid.clone(),
+                        relay_count: record.relays.len(),
+                    }
+                }
+                Insertion::Replaced | Insertion::Known => continue,
+            };
+            // Sends never block; they only fail when nobody is subscribed
+            let _ = self.events.send(event);
+        }
+
+        self.prune(&mut slots_guard);
+        drop(slots_guard);
+        self.quarantine_bids(relay_url, too_far_ahead).await;
+    }
+
+    fn validate(&self, bid: &BidTrace) -> Result<(), BidValidationError> {
+        let Some(clock) = &self.slot_clock else {
+            return bid.validate();
+        };
+        bid.validate_for(clock)?;
+        // Bids for the next slot arrive shortly before it starts
+        let current_slot = clock.current_slot().unwrap_or_default();
+        if bid.slot > U256::from(current_slot.saturating_add(1)) {
+            return Err(BidValidationError::SlotTooFarAhead {
+                slot: bid.slot,
+                reference_slot: current_slot,
+            });
+        }
+        Ok(())
+    }
+
+    // Stores bids that failed validation and announces each rejection
+    async fn quarantine_bids(&self, relay_url: &str, rejected: Vec<QuarantinedBid>) {
+        if rejected.is_empty() {
+            return;
+        }
+        let mut quarantine_guard = self.quarantine.write().await;
+        let quarantined = &mut quarantine_guard
+            .entry(relay_url.to_string())
+            .or_default()
+            .bids;
+        for record in rejected {
+            let _ = self.events.send(BidEvent::BidRejected {
+                relay: relay_url.to_string(),
+                bid: record.bid.clone(),
+                error: record.error.to_string(),
+            });
+            if quarantined.len() == QUARANTINE_CAPACITY {
+                quarantined.pop_front();
+            }
+            quarantined.push_back(record);
+        }
+    }
+
+    // Stores response entries that are not bid traces and announces each of them
+    async fn quarantine_malformed(&self, relay_url: &str, malformed: Vec<MalformedBid>) {
+        if malformed.is_empty() {
+            return;
+        }
+        let mut quarantine_guard = self.quarantine.write().await;
+        let quarantined = &mut quarantine_guard
+            .entry(relay_url.to_string())
+            .or_default()
+            .malformed;
+        for record in malformed {
+            let _ = self.events.send(BidEvent::BidMalformed {
+                relay: relay_url.to_string(),
+                entry: record.entry.clone(),
+                error: record.error.clone(),
+            });
+            if quarantined.len() == QUARANTINE_CAPACITY {
+                quarantined.pop_front();
             }
+            quarantined.push_back(record);
         }
     }
 
+    // Bids `relay_url` reported that failed validation, oldest first
+    pub async fn quarantined_bids(&self, relay_url: &str) -> Vec<QuarantinedBid> {
+        self.quarantine
+            .read()
+            .await
+            .get(relay_url)
+            .map(|quarantined| quarantined.bids.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    // Entries `relay_url` reported that could not be decoded as bids, oldest first
+    pub async fn malformed_bids(&self, relay_url: &str) -> Vec<MalformedBid> {
+        self.quarantine
+            .read()
+            .await
+            .get(relay_url)
+            .map(|quarantined| quarantined.malformed.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    // Lowest slot still inside the retention window. The window ends at the newest
+    // retained slot, but never past the slot clock's current slot.
+    fn oldest_retained_slot(&self, slots: &BTreeMap<u64, SlotBids>) -> u64 {
+        let Some(&newest) = slots.keys().next_back() else {
+            return 0;
+        };
+        let anchor = match self.slot_clock.as_ref().and_then(SlotClock::current_slot) {
+            Some(current_slot) => newest.min(current_slot),
+            None => newest,
+        };
+        anchor.saturating_sub(self.retained_slots - 1)
+    }
+
+    // Drops every slot that fell out of the retention window
+    fn prune(&self, slots: &mut BTreeMap<u64, SlotBids>) {
+        let oldest = self.oldest_retained_slot(slots);
+        *slots = slots.split_off(&oldest);
+    }
+
+    // Highest bid of the newest slot
     pub async fn get_highest_bid(&self) -> Option<BidTrace> {
-        let all_bids_guard = self.all_bids.read().await;
-        all_bids_guard.peek().map(|b| b.0.clone())
+        let slots_guard = self.slots.read().await;
+        slots_guard
+            .values()
+            .next_back()
+            .and_then(|slot_bids| slot_bids.highest().map(|b| b.bid.clone()))
+    }
+
+    // Highest bid received for `slot`, if the slot is still retained
+    pub async fn get_highest_bid_for_slot(&self, slot: u64) -> Option<BidTrace> {
+        let slots_guard = self.slots.read().await;
+        slots_guard
+            .get(&slot)
+            .and_then(|slot_bids| slot_bids.highest().map(|b| b.bid.clone()))
+    }
+
+    // All bids received for `slot`, highest value first
+    pub async fn get_bids_for_slot(&self, slot: u64) -> Vec<BidTrace> {
+        self.collect_for_slot(slot, usize::MAX, |_| true).await
+    }
+
+    // The `k` highest bids for `slot`, highest value first
+    pub async fn top_k(&self, slot: u64, k: usize) -> Vec<BidTrace> {
+        self.collect_for_slot(slot, k, |_| true).await
+    }
+
+    // Bids for `slot` strictly above `value`, highest value first
+    pub async fn bids_above(&self, slot: u64, value: U256) -> Vec<BidTrace> {
+        let Some(lower_bound) = value.checked_add(U256::from(1)) else {
+            return Vec::new();
+        };
+        let slots_guard = self.slots.read().await;
+        slots_guard
+            .get(&slot)
+            .map(|slot_bids| {
+                slot_bids
+                    .by_value
+                    .range((lower_bound, Reverse(u64::MAX))..)
+                    .rev()
+                    .map(|(_, key)| slot_bids.bids[key].record.bid.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Bids for `slot` submitted by `builder_pubkey`, highest value first
+    pub async fn bids_by_builder(&self, slot: u64, builder_pubkey: &BlsPublicKey) -> Vec<BidTrace> {
+        self.collect_for_slot(slot, usize::MAX, |bid| {
+            bid.builder_pubkey == *builder_pubkey
+        })
+        .await
+    }
+
+    // All bids for `slot` with the relays that reported them, highest value first
+    pub async fn get_relay_bids_for_slot(&self, slot: u64) -> Vec<RelayBid> {
+        let slots_guard = self.slots.read().await;
+        slots_guard
+            .get(&slot)
+            .map(|slot_bids| slot_bids.descending().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    // Bids for `slot` reported by at least one relay of `kind`, highest value first
+    pub async fn get_relay_bids_by_kind(&self, slot: u64, kind: RelayKind) -> Vec<RelayBid> {
+        let slots_guard = self.slots.read().await;
+        slots_guard
+            .get(&slot)
+            .map(|slot_bids| {
+                slot_bids
+                    .descending()
+                    .filter(|relay_bid| relay_bid.relay_kinds().contains(&kind))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // The bid identified by `key` with the relays that reported it
+    pub async fn get_relay_bid(&self, key: &BidKey) -> Option<RelayBid> {
+        let slots_guard = self.slots.read().await;
+        slots_guard
+            .get(&key.slot)
+            .and_then(|slot_bids| slot_bids.get(key).cloned())
+    }
+
+    async fn collect_for_slot<F>(&self, slot: u64, limit: usize, filter: F) -> Vec<BidTrace>
+    where
+        F: Fn(&BidTrace) -> bool,
+    {
+        let slots_guard = self.slots.read().await;
+        slots_guard
+            .get(&slot)
+            .map(|slot_bids| {
+                slot_bids
+                    .descending()
+                    .map(|relay_bid| &relay_bid.bid)
+                    .filter(|bid| filter(bid))
+                    .take(limit)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    // Slots currently retained, oldest first
+    pub async fn slots(&self) -> Vec<u64> {
+        let slots_guard = self.slots.read().await;
+        slots_guard.keys().copied().collect()
+    }
+
+    pub async fn clear_slot(&self, slot: u64) {
+        let mut slots_guard = self.slots.write().await;
+        slots_guard.remove(&slot);
     }
 
     pub async fn clear_all(&self) {
-        let mut all_bids_guard = self.all_bids.write().await;
-        let mut highest_bid_guard = self.highest_bid.write().await;
-        let mut unique_bids_guard = self.unique_bids.write().await;
+        let mut slots_guard = self.slots.write().await;
+        slots_guard.clear();
+    }
+
+    // Ends the auction for `slot` and announces its current top bid as the winner.
+    // The slot's bids are kept until the retention window prunes them.
+    pub async fn close_slot(&self, slot: u64) -> Option<RelayBid> {
+        let slots_guard = self.slots.read().await;
+        let winner = slots_guard
+            .get(&slot)
+            .and_then(|slot_bids| slot_bids.highest().cloned());
+        let _ = self.events.send(BidEvent::SlotClosed {
+            slot,
+            winner: winner.clone(),
+        });
+        winner
+    }
+
+    // Announces that `relay_url` could not be queried
+    pub fn report_relay_error(&self, relay_url: &str, slot: Option<u64>, error: &RelayError) {
+        let _ = self.events.send(BidEvent::RelayError {
+            relay: relay_url.to_string(),
+            slot,
+            error: error.to_string(),
+        });
+    }
 
-        all_bids_guard.clear();
-        unique_bids_guard.clear();
-        *highest_bid_guard = None;
+    // Announces the watcher's progress, e.g. `SlotWatched`, to subscribers
+    pub(crate) fn report_status(&self, event: BidEvent) {
+        let _ = self.events.send(event);
+    }
+
+    // Subscribe to bid events matching `filter`
+    pub fn subscribe(&self, filter: BidFilter) -> BidReceiver {
+        BidReceiver::new(self.events.subscribe(), filter)
     }
 
     // Subscribe to new top block bids
-    pub async fn subscribe_to_top_bids(&self) -> Receiver<BidTrace> {
-        let (tx, rx) = mpsc::channel(100);
-        let mut subscribers_guard = self.top_bid_subscribers.write().await;
-        subscribers_guard.push(tx);
-        rx
+    pub fn subscribe_to_top_bids(&self) -> BidReceiver {
+        self.subscribe(BidFilter::new().new_top_only())
     }
 
     // Subscribe to all new block bids
-    pub async fn subscribe_to_all_new_bids(&self) -> Receiver<BidTrace> {
-        let (tx, rx) = mpsc::channel(100);
-        let mut subscribers_guard = self.new_bid_subscribers.write().await;
-        subscribers_guard.push(tx);
-        rx
+    pub fn subscribe_to_all_new_bids(&self) -> BidReceiver {
+        self.subscribe(BidFilter::new().new_bids_only())
+    }
+
+    // Stream of bid events matching `filter`. Like `BidReceiver`, a stream that falls
+    // behind skips the oldest events instead of blocking ingestion.
+    pub fn stream(&self, filter: BidFilter) -> BidStream {
+        BidStream {
+            events: BroadcastStream::new(self.events.subscribe()),
+            filter,
+            missed: 0,
+        }
+    }
+
+    // Number of live subscriptions and streams; dropped ones are not counted
+    pub fn subscriber_count(&self) -> usize {
+        self.events.receiver_count()
+    }
+}
+
+// Receiving half of a bid subscription. A subscriber that falls more than
+// `SUBSCRIPTION_CAPACITY` events behind skips the oldest ones; the number skipped
+// is reported by `missed`. Dropping the receiver unsubscribes it.
+pub struct BidReceiver {
+    rx: broadcast::Receiver<BidEvent>,
+    filter: BidFilter,
+    missed: u64,
+}
+
+impl BidReceiver {
+    fn new(rx: broadcast::Receiver<BidEvent>, filter: BidFilter) -> Self {
+        Self {
+            rx,
+            filter,
+            missed: 0,
+        }
+    }
+
+    // Waits for the next matching event. Returns `None` once every clone of the bid
+    // manager is dropped.
+    pub async fn recv(&mut self) -> Option<BidEvent> {
+        loop {
+            match self.rx.recv().await {
+                Ok(event) if self.filter.matches(&event) => return Some(event),
+                Ok(_) => (),
+                Err(RecvError::Lagged(skipped)) => self.missed += skipped,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    // Returns the next matching event if one is already queued
+    pub fn try_recv(&mut self) -> Option<BidEvent> {
+        loop {
+            match self.rx.try_recv() {
+                Ok(event) if self.filter.matches(&event) => return Some(event),
+                Ok(_) => (),
+                Err(TryRecvError::Lagged(skipped)) => self.missed += skipped,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return None,
+            }
+        }
+    }
+
+    // Total number of events skipped because this subscriber lagged behind. Events
+    // the filter would have rejected are counted too.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+}
+
+// Stream of bid events matching a filter, the `Stream` counterpart of `BidReceiver`.
+// Events skipped because the stream lagged behind are counted by `missed`.
+pub struct BidStream {
+    events: BroadcastStream<BidEvent>,
+    filter: BidFilter,
+    missed: u64,
+}
+
+impl BidStream {
+    // Total number of events skipped because this stream lagged behind. Events the
+    // filter would have rejected are counted too.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+}
+
+impl Stream for BidStream {
+    type Item = BidEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match Pin::new(&mut self.events).poll_next(cx) {
+                Poll::Ready(Some(Ok(event))) if self.filter.matches(&event) => {
+                    return Poll::Ready(Some(event))
+                }
+                Poll::Ready(Some(Ok(_))) => (),
+                Poll::Ready(Some(Err(BroadcastStreamRecvError::Lagged(skipped)))) => {
+                    self.missed += skipped
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
     }
 }
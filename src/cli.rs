@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use block_bid_watcher::{config::OutputFormat, network::Network};
+use clap::{Args, Parser, Subcommand};
+
+// Command line interface of the watcher. Global flags override the configuration file.
+#[derive(Parser, Debug, Clone)]
+#[command(version, about = "Watches MEV-Boost relays for block builder bids")]
+pub struct Cli {
+    /// Configuration file (.toml or .json); the default mainnet relays are used without one
+    #[arg(long, short, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Network to follow: mainnet, sepolia or holesky
+    #[arg(long, global = true)]
+    pub network: Option<Network>,
+
+    /// Output format: text or json
+    #[arg(long, short, global = true)]
+    pub output: Option<OutputFormat>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Follow the chain and print bid events as relays report them (default). Relays
+    /// are reloaded whenever the configuration file changes.
+    Watch,
+    /// Fetch the bids every relay received for a slot or block, merged across relays
+    Fetch(FetchArgs),
+    /// Show the payload each relay delivered to the proposer of a slot
+    Delivered {
+        #[arg(long)]
+        slot: u64,
+    },
+    /// Inspect the configured relays
+    Relays {
+        #[command(subcommand)]
+        command: RelaysCommand,
+    },
+    /// Replay bids recorded as JSON lines, e.g. by `watch --output json`
+    Replay { file: PathBuf },
+}
+
+#[derive(Args, Debug, Clone)]
+#[group(required = true, multiple = false)]
+pub struct FetchArgs {
+    #[arg(long)]
+    pub slot: Option<u64>,
+    #[arg(long)]
+    pub block: Option<u64>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum RelaysCommand {
+    /// Probe every relay once and report its latency or error
+    Check,
+}
@@ -0,0 +1,466 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::Deserialize;
+use url::Url;
+
+use crate::{
+    bid_manager::{BidManager, MergePolicy, DEFAULT_RETAINED_SLOTS},
+    network::Network,
+    relay_config::{Endpoint, RelayConfig, RelayConfigError, RelayKind},
+    slot_clock::{PollPhase, PollSchedule, SlotOffset, SlotWindow},
+};
+
+// Errors found while loading or validating a configuration file
+#[derive(Debug)]
+pub enum ConfigError {
+    // The file could not be read
+    Io { path: PathBuf, source: io::Error },
+    // The file is not valid TOML or JSON, or does not match the expected layout
+    Parse { path: PathBuf, message: String },
+    // The file extension is neither `.toml` nor `.json`
+    UnsupportedFormat(PathBuf),
+    // No relays are configured
+    NoRelays,
+    // A relay URL is empty, cannot be parsed or is not an http(s) URL
+    InvalidRelayUrl { url: String, reason: String },
+    // The same relay is configured more than once
+    DuplicateRelay(String),
+    // The beacon node URL is empty, cannot be parsed or is not an http(s) URL
+    InvalidBeaconUrl { url: String, reason: String },
+    // A relay header cannot be sent over HTTP
+    InvalidRelay(RelayConfigError),
+    // A setting is out of range
+    InvalidValue { field: &'static str, reason: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "failed to read {}: {}", path.display(), source)
+            }
+            ConfigError::Parse { path, message } => {
+                write!(f, "failed to parse {}: {}", path.display(), message)
+            }
+            ConfigError::UnsupportedFormat(path) => write!(
+                f,
+                "unsupported config format {}; expected a .toml or .json file",
+                path.display()
+            ),
+            ConfigError::NoRelays => write!(f, "no relays configured"),
+            ConfigError::InvalidRelayUrl { url, reason } => {
+                write!(f, "invalid relay URL {:?}: {}", url, reason)
+            }
+            ConfigError::DuplicateRelay(url) => write!(f, "relay {} is configured twice", url),
+            ConfigError::InvalidBeaconUrl { url, reason } => {
+                write!(f, "invalid beacon node URL {:?}: {}", url, reason)
+            }
+            ConfigError::InvalidRelay(err) => write!(f, "{}", err),
+            ConfigError::InvalidValue { field, reason } => {
+                write!(f, "invalid {}: {}", field, reason)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            ConfigError::InvalidRelay(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<RelayConfigError> for ConfigError {
+    fn from(err: RelayConfigError) -> Self {
+        ConfigError::InvalidRelay(err)
+    }
+}
+
+// Parses `url` as an http(s) URL, the only schemes relays and beacon nodes serve
+fn parse_http_url(url: &str) -> Result<Url, String> {
+    if url.trim().is_empty() {
+        return Err("URL is empty".to_string());
+    }
+    let parsed = Url::parse(url.trim()).map_err(|err| err.to_string())?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("unsupported scheme {}", parsed.scheme()));
+    }
+    if parsed.host_str().is_none() {
+        return Err("URL has no host".to_string());
+    }
+    Ok(parsed)
+}
+
+// A relay entry in the configuration file
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RelayEntry {
+    pub url: String,
+    #[serde(default)]
+    pub kind: RelayKind,
+    pub timeout_ms: Option<u64>,
+    pub max_requests_per_second: Option<u32>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    // Sent as the Authorization header
+    pub api_key: Option<String>,
+    // Endpoints the relay is queried on; the Data API endpoints when unset
+    pub endpoints: Option<BTreeSet<Endpoint>>,
+}
+
+impl RelayEntry {
+    pub fn new(url: &str, kind: RelayKind) -> Self {
+        Self {
+            url: url.to_string(),
+            kind,
+            timeout_ms: None,
+            max_requests_per_second: None,
+            headers: BTreeMap::new(),
+            api_key: None,
+            endpoints: None,
+        }
+    }
+
+    pub fn relay_config(&self) -> RelayConfig {
+        let mut config = RelayConfig::new(self.url.trim().to_string()).with_kind(self.kind);
+        if let Some(timeout_ms) = self.timeout_ms {
+            config = config.with_request_timeout(Duration::from_millis(timeout_ms));
+        }
+        if let Some(max_requests_per_second) = self.max_requests_per_second {
+            config = config.with_max_requests_per_second(max_requests_per_second);
+        }
+        for (name, value) in &self.headers {
+            config = config.with_header(name, value);
+        }
+        if let Some(api_key) = &self.api_key {
+            config = config.with_api_key(api_key);
+        }
+        if let Some(endpoints) = &self.endpoints {
+            config = config.with_endpoints(endpoints.iter().copied());
+        }
+        config
+    }
+}
+
+// Where slot announcements come from
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum EventSourceConfig {
+    // Derive slots from the network's genesis time
+    #[default]
+    SlotClock,
+    // Follow a beacon node's payload_attributes events
+    Beacon {
+        url: String,
+    },
+}
+
+// A polling phase, starting `from_ms` milliseconds relative to the slot start
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PollPhaseEntry {
+    pub from_ms: i64,
+    pub interval_ms: u64,
+}
+
+// When and how often relays are polled within a slot. Phases default to
+// `PollSchedule::default()`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PollingConfig {
+    pub lead_ms: u64,
+    pub deadline_ms: u64,
+    pub phases: Vec<PollPhaseEntry>,
+}
+
+impl Default for PollingConfig {
+    fn default() -> Self {
+        let window = SlotWindow::default();
+        Self {
+            lead_ms: window.lead.as_millis() as u64,
+            deadline_ms: window.deadline.as_millis() as u64,
+            phases: Vec::new(),
+        }
+    }
+}
+
+impl PollingConfig {
+    pub fn slot_window(&self) -> SlotWindow {
+        SlotWindow {
+            lead: Duration::from_millis(self.lead_ms),
+            deadline: Duration::from_millis(self.deadline_ms),
+        }
+    }
+
+    pub fn poll_schedule(&self) -> PollSchedule {
+        if self.phases.is_empty() {
+            return PollSchedule::default();
+        }
+        PollSchedule::new(
+            self.phases
+                .iter()
+                .map(|phase| PollPhase {
+                    from: if phase.from_ms < 0 {
+                        SlotOffset::before(Duration::from_millis(phase.from_ms.unsigned_abs()))
+                    } else {
+                        SlotOffset::after(Duration::from_millis(phase.from_ms as u64))
+                    },
+                    interval: Duration::from_millis(phase.interval_ms),
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    // One human-readable line per event
+    #[default]
+    Text,
+    // One JSON object per line
+    Json,
+}
+
+// Which bid events are written out
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputEvents {
+    #[default]
+    TopBids,
+    NewBids,
+    All,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "unknown output format {}; expected text or json",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub format: OutputFormat,
+    pub events: OutputEvents,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetentionConfig {
+    // Number of most recent slots whose bids are kept
+    pub slots: u64,
+    pub merge_policy: MergePolicy,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            slots: DEFAULT_RETAINED_SLOTS,
+            merge_policy: MergePolicy::default(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    pub fn bid_manager(&self) -> BidManager {
+        BidManager::with_retention(self.slots).with_merge_policy(self.merge_policy)
+    }
+}
+
+// Settings of the watcher, loaded from a TOML or JSON file. The default watches
+// the mainnet relays.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub network: Network,
+    // Relays to watch; the network's default relays when unset
+    pub relays: Option<Vec<RelayEntry>>,
+    #[serde(default)]
+    pub event_source: EventSourceConfig,
+    #[serde(default)]
+    pub polling: PollingConfig,
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+}
+
+impl Config {
+    // Reads and validates the file at `path`, picking the format from its extension
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let contents = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|source| ConfigError::Io {
+                path: path.to_path_buf(),
+                source,
+            })?;
+
+        let parse_error = |message: String| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message,
+        };
+        let config: Config = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => {
+                toml::from_str(&contents).map_err(|err| parse_error(err.to_string()))?
+            }
+            Some("json") => {
+                serde_json::from_str(&contents).map_err(|err| parse_error(err.to_string()))?
+            }
+            _ => return Err(ConfigError::UnsupportedFormat(path.to_path_buf())),
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    // Checks every setting and returns the first problem found
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.validate_network()?;
+
+        let relays = self.relay_entries();
+        if relays.is_empty() {
+            return Err(ConfigError::NoRelays);
+        }
+
+        let mut seen = BTreeSet::new();
+        for relay in &relays {
+            let url =
+                parse_http_url(&relay.url).map_err(|reason| ConfigError::InvalidRelayUrl {
+                    url: relay.url.clone(),
+                    reason,
+                })?;
+            // `Url` lowercases the host, so differently cased URLs still collide
+            let normalized = url.as_str().trim_end_matches('/').to_string();
+            if !seen.insert(normalized.clone()) {
+                return Err(ConfigError::DuplicateRelay(normalized));
+            }
+            if relay.timeout_ms == Some(0) {
+                return Err(ConfigError::InvalidValue {
+                    field: "relays.timeout_ms",
+                    reason: format!("timeout for {} must be positive", relay.url),
+                });
+            }
+            if relay.max_requests_per_second == Some(0) {
+                return Err(ConfigError::InvalidValue {
+                    field: "relays.max_requests_per_second",
+                    reason: format!("rate limit for {} must be positive", relay.url),
+                });
+            }
+            if relay.endpoints.as_ref().is_some_and(BTreeSet::is_empty) {
+                return Err(ConfigError::InvalidValue {
+                    field: "relays.endpoints",
+                    reason: format!("{} has no endpoints enabled", relay.url),
+                });
+            }
+            relay.relay_config().header_map()?;
+        }
+
+        if let EventSourceConfig::Beacon { url } = &self.event_source {
+            parse_http_url(url).map_err(|reason| ConfigError::InvalidBeaconUrl {
+                url: url.clone(),
+                reason,
+            })?;
+        }
+
+        if self.polling.lead_ms == 0 && self.polling.deadline_ms == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "polling",
+                reason: "lead_ms and deadline_ms leave no time to poll".to_string(),
+            });
+        }
+        if self
+            .polling
+            .phases
+            .iter()
+            .any(|phase| phase.interval_ms == 0)
+        {
+            return Err(ConfigError::InvalidValue {
+                field: "polling.phases.interval_ms",
+                reason: "poll intervals must be positive".to_string(),
+            });
+        }
+
+        if self.retention.slots == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "retention.slots",
+                reason: "at least one slot must be retained".to_string(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn validate_network(&self) -> Result<(), ConfigError> {
+        let spec = self.network.spec();
+        if spec.seconds_per_slot == 0 || spec.slots_per_epoch == 0 {
+            return Err(ConfigError::InvalidValue {
+                field: "network",
+                reason: "slots and epochs must have a positive length".to_string(),
+            });
+        }
+
+        let is_fork_version = |version: &str| {
+            version
+                .strip_prefix("0x")
+                .is_some_and(|hex| hex.len() == 8 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        };
+        let versions = std::iter::once(spec.genesis_fork_version.as_str())
+            .chain(spec.forks.iter().map(|fork| fork.version.as_str()));
+        for version in versions {
+            if !is_fork_version(version) {
+                return Err(ConfigError::InvalidValue {
+                    field: "network.forks",
+                    reason: format!("{} is not a 4-byte hex fork version", version),
+                });
+            }
+        }
+        if spec
+            .forks
+            .windows(2)
+            .any(|pair| pair[0].epoch > pair[1].epoch)
+        {
+            return Err(ConfigError::InvalidValue {
+                field: "network.forks",
+                reason: "forks must be listed in activation order".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    // The configured relays, or the network's default relays when none are configured
+    pub fn relay_entries(&self) -> Vec<RelayEntry> {
+        match &self.relays {
+            Some(relays) => relays.clone(),
+            None => self.network.spec().default_relays,
+        }
+    }
+
+    pub fn relay_configs(&self) -> Vec<RelayConfig> {
+        self.relay_entries()
+            .iter()
+            .map(RelayEntry::relay_config)
+            .collect()
+    }
+}
@@ -0,0 +1,489 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use alloy_primitives::{Address, Bloom, Bytes, FixedBytes, B256, U256};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    network::NetworkSpec,
+    types::{deserialize_u256, serialize_u256_as_string, BidTrace, BlsPublicKey},
+};
+
+// Blob gas consumed by each blob, fixed since Deneb
+pub const GAS_PER_BLOB: u64 = 131072;
+
+// A KZG commitment to one blob
+pub type KzgCommitment = FixedBytes<48>;
+
+// A BLS12-381 signature
+pub type BlsSignature = FixedBytes<96>;
+
+// Forks with builder bids this crate can decode. Bids before Capella are not supported.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ForkName {
+    Capella,
+    Deneb,
+    Electra,
+}
+
+impl ForkName {
+    // True when blocks of this fork can carry blobs
+    pub fn has_blobs(self) -> bool {
+        self >= ForkName::Deneb
+    }
+
+    // True when blocks of this fork carry execution layer requests
+    pub fn has_execution_requests(self) -> bool {
+        self >= ForkName::Electra
+    }
+}
+
+impl FromStr for ForkName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "capella" => Ok(ForkName::Capella),
+            "deneb" => Ok(ForkName::Deneb),
+            "electra" => Ok(ForkName::Electra),
+            _ => Err(format!(
+                "unsupported fork {}; expected capella, deneb or electra",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ForkName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForkName::Capella => write!(f, "capella"),
+            ForkName::Deneb => write!(f, "deneb"),
+            ForkName::Electra => write!(f, "electra"),
+        }
+    }
+}
+
+// Errors returned while decoding a fork-versioned builder bid
+#[derive(Debug)]
+pub enum ForkError {
+    // The network has no supported fork active at the slot
+    UnsupportedSlot(u64),
+    // The response names a different fork than the network schedule gives for the slot
+    VersionMismatch {
+        expected: String,
+        actual: String,
+    },
+    // The response does not match the types of its fork
+    Decode {
+        fork: ForkName,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for ForkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForkError::UnsupportedSlot(slot) => {
+                write!(f, "no supported fork is active at slot {}", slot)
+            }
+            ForkError::VersionMismatch { expected, actual } => {
+                write!(f, "expected a {} bid, got version {}", expected, actual)
+            }
+            ForkError::Decode { fork, source } => {
+                write!(f, "invalid {} builder bid: {}", fork, source)
+            }
+        }
+    }
+}
+
+impl Error for ForkError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ForkError::Decode { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// Define the ExecutionPayloadHeaderCapella struct, the header of a Capella block
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ExecutionPayloadHeaderCapella {
+    pub parent_hash: B256,
+    pub fee_recipient: Address,
+    pub state_root: B256,
+    pub receipts_root: B256,
+    pub logs_bloom: Bloom,
+    pub prev_randao: B256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub block_number: U256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub gas_limit: U256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub gas_used: U256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub timestamp: U256,
+    pub extra_data: Bytes,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub base_fee_per_gas: U256,
+    pub block_hash: B256,
+    pub transactions_root: B256,
+    pub withdrawals_root: B256,
+}
+
+// Define the ExecutionPayloadHeaderDeneb struct, which adds blob gas accounting.
+// Electra headers have the same fields.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ExecutionPayloadHeaderDeneb {
+    pub parent_hash: B256,
+    pub fee_recipient: Address,
+    pub state_root: B256,
+    pub receipts_root: B256,
+    pub logs_bloom: Bloom,
+    pub prev_randao: B256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub block_number: U256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub gas_limit: U256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub gas_used: U256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub timestamp: U256,
+    pub extra_data: Bytes,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub base_fee_per_gas: U256,
+    pub block_hash: B256,
+    pub transactions_root: B256,
+    pub withdrawals_root: B256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub blob_gas_used: U256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub excess_blob_gas: U256,
+}
+
+// An execution payload header of any supported fork
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub enum ExecutionPayloadHeader {
+    Capella(ExecutionPayloadHeaderCapella),
+    Deneb(ExecutionPayloadHeaderDeneb),
+    Electra(ExecutionPayloadHeaderDeneb),
+}
+
+impl ExecutionPayloadHeader {
+    pub fn fork(&self) -> ForkName {
+        match self {
+            ExecutionPayloadHeader::Capella(_) => ForkName::Capella,
+            ExecutionPayloadHeader::Deneb(_) => ForkName::Deneb,
+            ExecutionPayloadHeader::Electra(_) => ForkName::Electra,
+        }
+    }
+
+    pub fn block_hash(&self) -> B256 {
+        match self {
+            ExecutionPayloadHeader::Capella(header) => header.block_hash,
+            ExecutionPayloadHeader::Deneb(header) | ExecutionPayloadHeader::Electra(header) => {
+                header.block_hash
+            }
+        }
+    }
+
+    pub fn block_number(&self) -> U256 {
+        match self {
+            ExecutionPayloadHeader::Capella(header) => header.block_number,
+            ExecutionPayloadHeader::Deneb(header) | ExecutionPayloadHeader::Electra(header) => {
+                header.block_number
+            }
+        }
+    }
+
+    // Blob gas used by the block, or `None` before Deneb
+    pub fn blob_gas_used(&self) -> Option<U256> {
+        match self {
+            ExecutionPayloadHeader::Capella(_) => None,
+            ExecutionPayloadHeader::Deneb(header) | ExecutionPayloadHeader::Electra(header) => {
+                Some(header.blob_gas_used)
+            }
+        }
+    }
+
+    // Excess blob gas after the block, or `None` before Deneb
+    pub fn excess_blob_gas(&self) -> Option<U256> {
+        match self {
+            ExecutionPayloadHeader::Capella(_) => None,
+            ExecutionPayloadHeader::Deneb(header) | ExecutionPayloadHeader::Electra(header) => {
+                Some(header.excess_blob_gas)
+            }
+        }
+    }
+}
+
+// A validator deposit made on the execution layer (EIP-6110)
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct DepositRequest {
+    pub pubkey: BlsPublicKey,
+    pub withdrawal_credentials: B256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub amount: U256,
+    pub signature: BlsSignature,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub index: U256,
+}
+
+// A withdrawal triggered from the execution layer (EIP-7002)
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct WithdrawalRequest {
+    pub source_address: Address,
+    pub validator_pubkey: BlsPublicKey,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub amount: U256,
+}
+
+// A validator consolidation triggered from the execution layer (EIP-7251)
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ConsolidationRequest {
+    pub source_address: Address,
+    pub source_pubkey: BlsPublicKey,
+    pub target_pubkey: BlsPublicKey,
+}
+
+// Execution layer requests carried by an Electra block
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct ExecutionRequests {
+    pub deposits: Vec<DepositRequest>,
+    pub withdrawals: Vec<WithdrawalRequest>,
+    pub consolidations: Vec<ConsolidationRequest>,
+}
+
+impl ExecutionRequests {
+    pub fn len(&self) -> usize {
+        self.deposits.len() + self.withdrawals.len() + self.consolidations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Define the BuilderBidCapella struct, a builder's bid as returned by `getHeader`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct BuilderBidCapella {
+    pub header: ExecutionPayloadHeaderCapella,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub value: U256,
+    pub pubkey: BlsPublicKey,
+}
+
+// Define the BuilderBidDeneb struct, which commits to the block's blobs
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct BuilderBidDeneb {
+    pub header: ExecutionPayloadHeaderDeneb,
+    pub blob_kzg_commitments: Vec<KzgCommitment>,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub value: U256,
+    pub pubkey: BlsPublicKey,
+}
+
+// Define the BuilderBidElectra struct, which adds the block's execution requests
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct BuilderBidElectra {
+    pub header: ExecutionPayloadHeaderDeneb,
+    pub blob_kzg_commitments: Vec<KzgCommitment>,
+    pub execution_requests: ExecutionRequests,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub value: U256,
+    pub pubkey: BlsPublicKey,
+}
+
+// A builder bid of any supported fork
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+#[serde(untagged)]
+pub enum BuilderBid {
+    Capella(BuilderBidCapella),
+    Deneb(BuilderBidDeneb),
+    Electra(BuilderBidElectra),
+}
+
+impl BuilderBid {
+    // Decodes a bid message with the types of `fork`
+    pub fn from_value(fork: ForkName, message: Value) -> Result<Self, ForkError> {
+        match fork {
+            ForkName::Capella => decode(fork, message).map(BuilderBid::Capella),
+            ForkName::Deneb => decode(fork, message).map(BuilderBid::Deneb),
+            ForkName::Electra => decode(fork, message).map(BuilderBid::Electra),
+        }
+    }
+
+    pub fn fork(&self) -> ForkName {
+        match self {
+            BuilderBid::Capella(_) => ForkName::Capella,
+            BuilderBid::Deneb(_) => ForkName::Deneb,
+            BuilderBid::Electra(_) => ForkName::Electra,
+        }
+    }
+
+    pub fn header(&self) -> ExecutionPayloadHeader {
+        match self {
+            BuilderBid::Capella(bid) => ExecutionPayloadHeader::Capella(bid.header.clone()),
+            BuilderBid::Deneb(bid) => ExecutionPayloadHeader::Deneb(bid.header.clone()),
+            BuilderBid::Electra(bid) => ExecutionPayloadHeader::Electra(bid.header.clone()),
+        }
+    }
+
+    pub fn value(&self) -> U256 {
+        match self {
+            BuilderBid::Capella(bid) => bid.value,
+            BuilderBid::Deneb(bid) => bid.value,
+            BuilderBid::Electra(bid) => bid.value,
+        }
+    }
+
+    pub fn pubkey(&self) -> BlsPublicKey {
+        match self {
+            BuilderBid::Capella(bid) => bid.pubkey,
+            BuilderBid::Deneb(bid) => bid.pubkey,
+            BuilderBid::Electra(bid) => bid.pubkey,
+        }
+    }
+
+    // Commitments to the blobs of the block; empty before Deneb
+    pub fn blob_kzg_commitments(&self) -> &[KzgCommitment] {
+        match self {
+            BuilderBid::Capella(_) => &[],
+            BuilderBid::Deneb(bid) => &bid.blob_kzg_commitments,
+            BuilderBid::Electra(bid) => &bid.blob_kzg_commitments,
+        }
+    }
+
+    pub fn blob_count(&self) -> usize {
+        self.blob_kzg_commitments().len()
+    }
+
+    // Execution requests of the block, or `None` before Electra
+    pub fn execution_requests(&self) -> Option<&ExecutionRequests> {
+        match self {
+            BuilderBid::Electra(bid) => Some(&bid.execution_requests),
+            _ => None,
+        }
+    }
+
+    // True when the header's blob gas is what its blob commitments account for
+    pub fn has_consistent_blob_gas(&self) -> bool {
+        let expected = U256::from(self.blob_count() as u64 * GAS_PER_BLOB);
+        self.header()
+            .blob_gas_used()
+            .is_none_or(|blob_gas_used| blob_gas_used == expected)
+    }
+
+    // True when this bid is the block a relay reported in `bid`
+    pub fn is_bid_for(&self, bid: &BidTrace) -> bool {
+        self.header().block_hash() == bid.block_hash
+            && self.pubkey() == bid.builder_pubkey
+            && self.value() == bid.value
+    }
+}
+
+fn decode<T: DeserializeOwned>(fork: ForkName, value: Value) -> Result<T, ForkError> {
+    serde_json::from_value(value).map_err(|source| ForkError::Decode { fork, source })
+}
+
+// Define the SignedBuilderBid struct, a builder bid signed by its relay
+#[derive(Serialize, Debug, PartialEq, Eq, Clone)]
+pub struct SignedBuilderBid {
+    pub message: BuilderBid,
+    pub signature: BlsSignature,
+}
+
+// A `getHeader` response before its message is decoded for a fork
+#[derive(Deserialize)]
+struct VersionedResponse {
+    version: Option<String>,
+    data: SignedMessage,
+}
+
+#[derive(Deserialize)]
+struct SignedMessage {
+    message: Value,
+    signature: BlsSignature,
+}
+
+impl SignedBuilderBid {
+    // Decodes a `getHeader` response for `slot`, using the fork the network schedules
+    // for that slot. A `version` in the response must agree with the schedule.
+    pub fn from_get_header(spec: &NetworkSpec, slot: u64, body: &str) -> Result<Self, ForkError> {
+        let fork = spec
+            .fork_name_at_slot(slot)
+            .ok_or(ForkError::UnsupportedSlot(slot))?;
+        let response: VersionedResponse =
+            serde_json::from_str(body).map_err(|source| ForkError::Decode { fork, source })?;
+        // Relays name the scheduled fork, which may be newer than the types decoding it
+        if let (Some(version), Some(scheduled)) = (response.version, spec.fork_at_slot(slot)) {
+            if !version.eq_ignore_ascii_case(&scheduled.name) {
+                return Err(ForkError::VersionMismatch {
+                    expected: scheduled.name.clone(),
+                    actual: version,
+                });
+            }
+        }
+
+        Ok(SignedBuilderBid {
+            message: BuilderBid::from_value(fork, response.data.message)?,
+            signature: response.data.signature,
+        })
+    }
+}
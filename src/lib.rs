@@ -1,4 +1,12 @@
+pub mod beacon_events;
 pub mod bid_manager;
+pub mod config;
+pub mod forks;
+pub mod network;
 pub mod relay_client;
 pub mod relay_clients;
+pub mod relay_config;
+pub mod relay_health;
+pub mod replay;
+pub mod slot_clock;
 pub mod types;
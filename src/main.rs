@@ -1,51 +1,295 @@
-use alloy_rs::prelude::*;
-use block_bid_watcher::relay_clients::RelayClients;
-use std::error::Error;
+mod cli;
 
-#[tokio::main]
+use block_bid_watcher::{
+    beacon_events::BeaconEventSource,
+    bid_manager::{BidFilter, BidManager},
+    config::{Config, EventSourceConfig, OutputEvents, OutputFormat},
+    relay_client::BidTraceQuery,
+    relay_clients::RelayClients,
+    replay::{self, read_replay_file},
+};
+use clap::Parser;
+use cli::{Cli, Command, FetchArgs, RelaysCommand};
+use serde::Serialize;
+use serde_json::json;
+use std::{
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::task::JoinHandle;
+
+// How often the configuration file is checked for changes while watching
+const CONFIG_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
 
-// TODO
-// Extract into seperate configuration file
-// Support Optimistic Relay vs Normal Relay vs XGA Relay
+#[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    // Initialize RelayClients with URLs
-    let mut relay_clients = RelayClients::new(vec![
-        "https://relay.ultrasound.money".to_string(),
-        "https://agnostic-relay.net".to_string(),
-        "https://boost-relay.flashbots.net".to_string(),
-        "https://bloxroute.max-profit.blxrbdn.com".to_string(),
-        "https://mainnet.aestus.live".to_string(),
-        "https://titanrelay.xyz".to_string(),
-    ]);
-
-    let mut bid_manager_receiver = relay_clients.bid_manager.subscribe_to_top_bids().await?;
-
-    // Spawn a task to handle received messages from the bid manager
+    let mut cli = Cli::parse();
+    let config = load_config(&cli).await?;
+
+    match cli.command.take().unwrap_or(Command::Watch) {
+        Command::Watch => watch(&cli, &config).await,
+        Command::Fetch(args) => fetch(&config, args).await,
+        Command::Delivered { slot } => delivered(&config, slot).await,
+        Command::Relays {
+            command: RelaysCommand::Check,
+        } => check_relays(&config).await,
+        Command::Replay { file } => replay(&config, file).await,
+    }
+}
+
+// Loads the configuration file if one is given, or watches the default mainnet
+// relays, then applies the global flags on top
+async fn load_config(cli: &Cli) -> Result<Config, Box<dyn Error>> {
+    let mut config = match &cli.config {
+        Some(path) => Config::load(path).await?,
+        None => Config::default(),
+    };
+    if let Some(network) = &cli.network {
+        config.network = network.clone();
+    }
+    if let Some(format) = cli.output {
+        config.output.format = format;
+    }
+    Ok(config)
+}
+
+async fn modified_at(path: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(path).await.ok()?.modified().ok()
+}
+
+// Reloads the relay list whenever the configuration file changes. Bids collected
+// so far and bid subscriptions are kept; other settings apply on restart.
+fn reload_relays_on_change(cli: &Cli, relay_clients: Arc<RelayClients>) {
+    let Some(path) = cli.config.clone() else {
+        return;
+    };
+    let cli = cli.clone();
+
+    tokio::spawn(async move {
+        let mut last_modified = modified_at(&path).await;
+        let mut interval = tokio::time::interval(CONFIG_RELOAD_INTERVAL);
+        loop {
+            interval.tick().await;
+            let modified = modified_at(&path).await;
+            if modified == last_modified {
+                continue;
+            }
+            last_modified = modified;
+
+            let configs = match load_config(&cli).await {
+                Ok(config) => config.relay_configs(),
+                Err(err) => {
+                    eprintln!("Keeping current relays, failed to reload config: {}", err);
+                    continue;
+                }
+            };
+            match relay_clients.reload_relays(configs).await {
+                Ok(change) if change.is_empty() => {}
+                Ok(change) => eprintln!("Reloaded relays: {}", change),
+                Err(err) => eprintln!("Keeping current relays, invalid relay config: {}", err),
+            }
+        }
+    });
+}
+
+// Bid manager with the configured retention that rejects bids submitted outside
+// their slot on the configured network
+fn bid_manager(config: &Config) -> BidManager {
+    config
+        .retention
+        .bid_manager()
+        .with_slot_clock(config.network.slot_clock())
+}
+
+fn relay_clients(config: &Config) -> Result<RelayClients, Box<dyn Error>> {
+    Ok(RelayClients::from_configs(config.relay_configs())?.with_bid_manager(bid_manager(config)))
+}
+
+// Subscribes to the bid events selected in the output settings and prints them
+// until the bid manager is dropped
+fn print_events(config: &Config, bid_manager: &BidManager) -> JoinHandle<()> {
+    let filter = match config.output.events {
+        OutputEvents::TopBids => BidFilter::default().new_top_only(),
+        OutputEvents::NewBids => BidFilter::default().new_bids_only(),
+        OutputEvents::All => BidFilter::default(),
+    };
+    let mut bid_manager_receiver = bid_manager.subscribe(filter);
+    let format = config.output.format;
+
     tokio::spawn(async move {
         while let Some(data) = bid_manager_receiver.recv().await {
-            println!("New Highest Bid: {}", data);
+            // Status events go to stderr, see `print_status`
+            if !data.is_status() {
+                print(format, &data, &data);
+            }
+        }
+        if bid_manager_receiver.missed() > 0 {
+            eprintln!("Missed {} events", bid_manager_receiver.missed());
+        }
+    })
+}
+
+// Logs the watcher's progress to stderr so stdout only carries bid events
+fn print_status(bid_manager: &BidManager) {
+    let mut status_receiver = bid_manager.subscribe(BidFilter::default());
+
+    tokio::spawn(async move {
+        while let Some(event) = status_receiver.recv().await {
+            if event.is_status() {
+                eprintln!("{}", event);
+            }
         }
     });
+}
 
-    // Connect to the WebSocket provider
-    // Hardcoded for meow
-    let provider =
-        AlloyProvider::connect("wss://mainnet.infura.io/ws/v3/97498194812e457a9305b7ac71dd724b")
-            .await?;
+// Prints `text` or, in JSON mode, `json` on a single line
+fn print(format: OutputFormat, text: &impl Display, json: &impl Serialize) {
+    match format {
+        OutputFormat::Text => println!("{}", text),
+        OutputFormat::Json => match serde_json::to_string(json) {
+            Ok(json) => println!("{}", json),
+            Err(err) => eprintln!("Failed to encode output: {}", err),
+        },
+    }
+}
 
-    // Subscribe to new blocks
-    let mut block_stream = provider.subscribe_blocks().await?;
+async fn watch(cli: &Cli, config: &Config) -> Result<(), Box<dyn Error>> {
+    let relay_clients = Arc::new(relay_clients(config)?);
+    print_events(config, &relay_clients.bid_manager);
+    print_status(&relay_clients.bid_manager);
+    reload_relays_on_change(cli, relay_clients.clone());
 
-    // Process new blocks as they come in
-    while let Some(block) = block_stream.next().await {
-        let block_number = block.number.expect("Block number not found in new block");
-        println!("New block: {}", block_number);
+    let clock = config.network.slot_clock();
+    let window = config.polling.slot_window();
+    let schedule = config.polling.poll_schedule();
 
-        // Poll for each new block
-        relay_clients
-            .poll_for(block_number + U64::one(), 1, 12)
+    // Report relays that are backing off or paused once per slot
+    let health = relay_clients.health.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(clock.slot_duration());
+        loop {
+            interval.tick().await;
+            for (relay_url, status, relay_health) in health.degraded(clock.current_slot()).await {
+                eprintln!("Relay {} is {} ({})", relay_url, status, relay_health);
+            }
+        }
+    });
+
+    match &config.event_source {
+        // Poll each slot on the exact parent hash its proposer builds on
+        EventSourceConfig::Beacon { url } => {
+            let source = BeaconEventSource::new(url.clone())?;
+            loop {
+                if let Err(err) = relay_clients
+                    .follow_beacon_events(&source, &clock, &window, &schedule)
+                    .await
+                {
+                    eprintln!("Beacon event stream error: {}", err);
+                }
+                // Reconnect after the stream ends or fails
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        }
+        // Follow the beacon chain slot clock, polling each slot by slot number from
+        // shortly before its boundary until the bid deadline
+        EventSourceConfig::SlotClock => relay_clients.run(&clock, &window, &schedule).await,
+    }
+
+    Ok(())
+}
+
+async fn fetch(config: &Config, args: FetchArgs) -> Result<(), Box<dyn Error>> {
+    let relay_clients = relay_clients(config)?;
+    let query = match (args.slot, args.block) {
+        (Some(slot), _) => BidTraceQuery::by_slot(slot),
+        (None, Some(block)) => BidTraceQuery::by_block_number(block),
+        (None, None) => unreachable!("clap requires --slot or --block"),
+    };
+
+    for (relay_url, result) in relay_clients.fetch_bids(&query).await {
+        match result {
+            Ok(response) => relay_clients.bid_manager.add_bid_response(response).await,
+            Err(err) => eprintln!("Relay {} failed: {}", relay_url, err),
+        }
+    }
+
+    // Print each slot's bids merged across relays, highest value first
+    for slot in relay_clients.bid_manager.slots().await {
+        for relay_bid in relay_clients
+            .bid_manager
+            .get_relay_bids_for_slot(slot)
             .await
+        {
+            print(config.output.format, &relay_bid, &relay_bid);
+        }
     }
+    Ok(())
+}
+
+async fn delivered(config: &Config, slot: u64) -> Result<(), Box<dyn Error>> {
+    let relay_clients = relay_clients(config)?;
+
+    for (relay_url, result) in relay_clients.get_delivered_payloads_for_slot(slot).await {
+        match result {
+            Ok(Some(payload)) => print(
+                config.output.format,
+                &format!("{}: {}", relay_url, payload),
+                &json!({ "relay": relay_url, "payload": payload }),
+            ),
+            Ok(None) => print(
+                config.output.format,
+                &format!("{}: no payload delivered for slot {}", relay_url, slot),
+                &json!({ "relay": relay_url, "payload": null }),
+            ),
+            Err(err) => eprintln!("Relay {} failed: {}", relay_url, err),
+        }
+    }
+    Ok(())
+}
+
+async fn check_relays(config: &Config) -> Result<(), Box<dyn Error>> {
+    let relay_clients = relay_clients(config)?;
+    let results = relay_clients
+        .check_relays(&config.network.slot_clock())
+        .await;
+
+    let mut failed = 0;
+    for (relay_url, result) in &results {
+        match result {
+            Ok(latency) => print(
+                config.output.format,
+                &format!("{}: ok in {} ms", relay_url, latency.as_millis()),
+                &json!({ "relay": relay_url, "ok": true, "latency_ms": latency.as_millis() as u64 }),
+            ),
+            Err(err) => {
+                failed += 1;
+                print(
+                    config.output.format,
+                    &format!("{}: {}", relay_url, err),
+                    &json!({ "relay": relay_url, "ok": false, "error": err.to_string() }),
+                )
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} relays failed", failed, results.len()).into());
+    }
+    Ok(())
+}
+
+async fn replay(config: &Config, file: PathBuf) -> Result<(), Box<dyn Error>> {
+    let records = read_replay_file(&file).await?;
+    let bid_manager = bid_manager(config);
+    let printer = print_events(config, &bid_manager);
+
+    replay::replay(&bid_manager, records).await;
 
+    // Dropping the bid manager ends the subscription once every event is printed
+    drop(bid_manager);
+    printer.await?;
     Ok(())
 }
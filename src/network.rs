@@ -0,0 +1,244 @@
+use std::{fmt, str::FromStr};
+
+use serde::Deserialize;
+
+use crate::{
+    config::RelayEntry,
+    forks::ForkName,
+    relay_config::RelayKind,
+    slot_clock::{
+        SlotClock, DEFAULT_SECONDS_PER_SLOT, HOLESKY_GENESIS_TIME, MAINNET_GENESIS_TIME,
+        SEPOLIA_GENESIS_TIME,
+    },
+};
+
+pub const DEFAULT_SLOTS_PER_EPOCH: u64 = 32;
+
+fn default_seconds_per_slot() -> u64 {
+    DEFAULT_SECONDS_PER_SLOT
+}
+
+fn default_slots_per_epoch() -> u64 {
+    DEFAULT_SLOTS_PER_EPOCH
+}
+
+// A scheduled fork: `version` is the 4-byte fork version as hex, active from `epoch`
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Fork {
+    pub name: String,
+    pub version: String,
+    pub epoch: u64,
+}
+
+impl Fork {
+    fn new(name: &str, version: &str, epoch: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            epoch,
+        }
+    }
+}
+
+// Chain parameters of a network and the relays watched on it by default
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkSpec {
+    pub name: String,
+    // Seconds since the Unix epoch
+    pub genesis_time: u64,
+    #[serde(default = "default_seconds_per_slot")]
+    pub seconds_per_slot: u64,
+    #[serde(default = "default_slots_per_epoch")]
+    pub slots_per_epoch: u64,
+    pub genesis_fork_version: String,
+    // Forks after genesis, in activation order
+    #[serde(default)]
+    pub forks: Vec<Fork>,
+    #[serde(default)]
+    pub default_relays: Vec<RelayEntry>,
+}
+
+impl NetworkSpec {
+    pub fn mainnet() -> Self {
+        Self {
+            name: "mainnet".to_string(),
+            genesis_time: MAINNET_GENESIS_TIME,
+            seconds_per_slot: DEFAULT_SECONDS_PER_SLOT,
+            slots_per_epoch: DEFAULT_SLOTS_PER_EPOCH,
+            genesis_fork_version: "0x00000000".to_string(),
+            forks: vec![
+                Fork::new("altair", "0x01000000", 74240),
+                Fork::new("bellatrix", "0x02000000", 144896),
+                Fork::new("capella", "0x03000000", 194048),
+                Fork::new("deneb", "0x04000000", 269568),
+                Fork::new("electra", "0x05000000", 364032),
+                Fork::new("fulu", "0x06000000", 411392),
+            ],
+            default_relays: vec![
+                RelayEntry::new("https://relay.ultrasound.money", RelayKind::Optimistic),
+                RelayEntry::new("https://agnostic-relay.net", RelayKind::Standard),
+                RelayEntry::new("https://boost-relay.flashbots.net", RelayKind::Standard),
+                RelayEntry::new(
+                    "https://bloxroute.max-profit.blxrbdn.com",
+                    RelayKind::Standard,
+                ),
+                RelayEntry::new("https://mainnet.aestus.live", RelayKind::Standard),
+                RelayEntry::new("https://titanrelay.xyz", RelayKind::Optimistic),
+            ],
+        }
+    }
+
+    pub fn sepolia() -> Self {
+        Self {
+            name: "sepolia".to_string(),
+            genesis_time: SEPOLIA_GENESIS_TIME,
+            seconds_per_slot: DEFAULT_SECONDS_PER_SLOT,
+            slots_per_epoch: DEFAULT_SLOTS_PER_EPOCH,
+            genesis_fork_version: "0x90000069".to_string(),
+            forks: vec![
+                Fork::new("altair", "0x90000070", 50),
+                Fork::new("bellatrix", "0x90000071", 100),
+                Fork::new("capella", "0x90000072", 56832),
+                Fork::new("deneb", "0x90000073", 132608),
+                Fork::new("electra", "0x90000074", 222464),
+                Fork::new("fulu", "0x90000075", 272640),
+            ],
+            default_relays: vec![RelayEntry::new(
+                "https://boost-relay-sepolia.flashbots.net",
+                RelayKind::Standard,
+            )],
+        }
+    }
+
+    pub fn holesky() -> Self {
+        Self {
+            name: "holesky".to_string(),
+            genesis_time: HOLESKY_GENESIS_TIME,
+            seconds_per_slot: DEFAULT_SECONDS_PER_SLOT,
+            slots_per_epoch: DEFAULT_SLOTS_PER_EPOCH,
+            genesis_fork_version: "0x01017000".to_string(),
+            forks: vec![
+                Fork::new("altair", "0x02017000", 0),
+                Fork::new("bellatrix", "0x03017000", 0),
+                Fork::new("capella", "0x04017000", 256),
+                Fork::new("deneb", "0x05017000", 29696),
+                Fork::new("electra", "0x06017000", 115968),
+                Fork::new("fulu", "0x07017000", 165120),
+            ],
+            default_relays: vec![
+                RelayEntry::new(
+                    "https://boost-relay-holesky.flashbots.net",
+                    RelayKind::Standard,
+                ),
+                RelayEntry::new("https://holesky.aestus.live", RelayKind::Standard),
+                RelayEntry::new("https://holesky.titanrelay.xyz", RelayKind::Optimistic),
+            ],
+        }
+    }
+
+    pub fn slot_clock(&self) -> SlotClock {
+        SlotClock::new(self.genesis_time, self.seconds_per_slot)
+    }
+
+    pub fn epoch_at_slot(&self, slot: u64) -> u64 {
+        slot / self.slots_per_epoch.max(1)
+    }
+
+    // Latest fork active at `slot`, or `None` before the first fork
+    pub fn fork_at_slot(&self, slot: u64) -> Option<&Fork> {
+        let epoch = self.epoch_at_slot(slot);
+        self.forks.iter().rev().find(|fork| fork.epoch <= epoch)
+    }
+
+    // Latest fork with builder bid types active at `slot`. Forks this crate has no
+    // types for are skipped, so a newer fork falls back to the latest supported one.
+    pub fn fork_name_at_slot(&self, slot: u64) -> Option<ForkName> {
+        let epoch = self.epoch_at_slot(slot);
+        self.forks
+            .iter()
+            .rev()
+            .filter(|fork| fork.epoch <= epoch)
+            .find_map(|fork| fork.name.parse().ok())
+    }
+
+    // Fork version in effect at `slot`
+    pub fn fork_version_at_slot(&self, slot: u64) -> &str {
+        self.fork_at_slot(slot)
+            .map_or(&self.genesis_fork_version, |fork| &fork.version)
+    }
+}
+
+// Helper accepting either a preset name or a full network table
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NetworkEntry {
+    Preset(String),
+    Custom(Box<NetworkSpec>),
+}
+
+impl TryFrom<NetworkEntry> for Network {
+    type Error = String;
+
+    fn try_from(entry: NetworkEntry) -> Result<Self, Self::Error> {
+        match entry {
+            NetworkEntry::Preset(name) => name.parse(),
+            NetworkEntry::Custom(spec) => Ok(Network::Custom(spec)),
+        }
+    }
+}
+
+// Network the watcher follows: a built-in preset or a custom devnet. In a
+// configuration file this is either a preset name or a table of `NetworkSpec` fields.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(try_from = "NetworkEntry")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Sepolia,
+    Holesky,
+    Custom(Box<NetworkSpec>),
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "sepolia" => Ok(Network::Sepolia),
+            "holesky" => Ok(Network::Holesky),
+            _ => Err(format!(
+                "unknown network {}; expected mainnet, sepolia or holesky",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Network::Mainnet => write!(f, "mainnet"),
+            Network::Sepolia => write!(f, "sepolia"),
+            Network::Holesky => write!(f, "holesky"),
+            Network::Custom(spec) => write!(f, "{}", spec.name),
+        }
+    }
+}
+
+impl Network {
+    pub fn spec(&self) -> NetworkSpec {
+        match self {
+            Network::Mainnet => NetworkSpec::mainnet(),
+            Network::Sepolia => NetworkSpec::sepolia(),
+            Network::Holesky => NetworkSpec::holesky(),
+            Network::Custom(spec) => spec.as_ref().clone(),
+        }
+    }
+
+    pub fn slot_clock(&self) -> SlotClock {
+        self.spec().slot_clock()
+    }
+}
@@ -1,46 +1,501 @@
-use alloy_rs::prelude::*;
-use block_bid_watcher::relay_clients::RelayClients;
-use std::error::Error;
-
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    // Initialize RelayClients with URLs
-    let mut relay_clients = RelayClients::new(vec![
-        "https://relay.ultrasound.money".to_string(),
-        "https://agnostic-relay.net".to_string(),
-        "https://boost-relay.flashbots.net".to_string(),
-        "https://bloxroute.max-profit.blxrbdn.com".to_string(),
-        "https://mainnet.aestus.live".to_string(),
-        "https://titanrelay.xyz".to_string(),
-    ]);
-
-    let mut bid_manager_receiver = relay_clients.bid_manager.subscribe_to_top_bids().await?;
-
-    // Spawn a task to handle received messages from the bid manager
-    tokio::spawn(async move {
-        while let Some(data) = bid_manager_receiver.recv().await {
-            println!("New Highest Bid: {}", data);
-        }
-    });
-
-    // Connect to the WebSocket provider
-    let provider =
-        AlloyProvider::connect("wss://mainnet.infura.io/ws/v3/97498194812e457a9305b7ac71dd724b")
+use std::{
+    collections::{BTreeSet, HashMap},
+    error::Error,
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use alloy_primitives::{B256, U256};
+use reqwest::{header::RETRY_AFTER, Client, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{value::RawValue, Value};
+use tokio::{
+    sync::Mutex,
+    time::{self, Instant},
+};
+
+use crate::{
+    relay_config::{Endpoint, RelayConfig, RelayConfigError, RelayKind},
+    types::{
+        BidDetails, BidResponse, BidTrace, BlsPublicKey, BuilderStatus, DeliveredPayload,
+        DeliveredPayloadResponse, MalformedBid, SignedValidatorRegistration,
+    },
+};
+
+// Path of the relay Data API endpoint listing bids received from block builders
+const BUILDER_BLOCKS_RECEIVED_PATH: &str = "/relay/v1/data/bidtraces/builder_blocks_received";
+// Path of the relay Data API endpoint listing payloads delivered to proposers
+const PROPOSER_PAYLOAD_DELIVERED_PATH: &str = "/relay/v1/data/bidtraces/proposer_payload_delivered";
+// Path of the relay Data API endpoint returning a validator's latest registration
+const VALIDATOR_REGISTRATION_PATH: &str = "/relay/v1/data/validator_registration";
+// Path of mev-boost-relay's internal API returning a builder's status, followed by
+// the builder's public key
+const BUILDER_STATUS_PATH: &str = "/internal/v1/builder";
+
+// How long a builder's status is reused before it is requested again: one mainnet
+// slot, so a demotion shows up on the next slot's bids
+const BUILDER_STATUS_TTL: Duration = Duration::from_secs(12);
+
+// Errors returned while querying a relay
+#[derive(Debug)]
+pub enum RelayError {
+    // The request could not be sent or the response body could not be read
+    Http(reqwest::Error),
+    // The relay answered with a non-success status code. `retry_after` holds the
+    // delay requested by a `Retry-After` header, given in seconds or as a date.
+    Status {
+        status: StatusCode,
+        body: String,
+        retry_after: Option<Duration>,
+    },
+    // The response body does not match the expected Data API format
+    Decode(serde_json::Error),
+    // The relay is configured not to be queried on this endpoint
+    EndpointDisabled(Endpoint),
+}
+
+impl fmt::Display for RelayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayError::Http(err) => write!(f, "relay request failed: {}", err),
+            RelayError::Status { status, body, .. } => {
+                write!(f, "relay returned {}: {}", status, body)
+            }
+            RelayError::Decode(err) => write!(f, "failed to decode relay response: {}", err),
+            RelayError::EndpointDisabled(endpoint) => {
+                write!(f, "endpoint {} is disabled for this relay", endpoint)
+            }
+        }
+    }
+}
+
+impl RelayError {
+    // Whether the relay may answer the same request later: transport failures,
+    // rate limiting and server errors are transient, anything else is not
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            RelayError::Http(_) => true,
+            RelayError::Status { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+            }
+            RelayError::Decode(_) | RelayError::EndpointDisabled(_) => false,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            RelayError::Status { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl Error for RelayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RelayError::Http(err) => Some(err),
+            RelayError::Status { .. } | RelayError::EndpointDisabled(_) => None,
+            RelayError::Decode(err) => Some(err),
+        }
+    }
+}
+
+impl From<reqwest::Error> for RelayError {
+    fn from(err: reqwest::Error) -> Self {
+        RelayError::Http(err)
+    }
+}
+
+impl From<serde_json::Error> for RelayError {
+    fn from(err: serde_json::Error) -> Self {
+        RelayError::Decode(err)
+    }
+}
+
+// Delay requested by a `Retry-After` value, either delta-seconds or an HTTP date.
+// A date in the past asks for no delay.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let retry_at = httpdate::parse_http_date(value).ok()?;
+    Some(
+        retry_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default(),
+    )
+}
+
+// Query filters for the builder_blocks_received endpoint. Unset filters are omitted.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct BidTraceQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<B256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builder_pubkey: Option<BlsPublicKey>,
+}
+
+impl BidTraceQuery {
+    pub fn by_slot(slot: u64) -> Self {
+        Self {
+            slot: Some(slot),
+            ..Default::default()
+        }
+    }
+
+    pub fn by_block_number(block_number: u64) -> Self {
+        Self {
+            block_number: Some(block_number),
+            ..Default::default()
+        }
+    }
+}
+
+// Query filters and pagination for the proposer_payload_delivered endpoint.
+// `cursor` is the highest slot to return; relays page backwards from it.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeliveredPayloadQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_hash: Option<B256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposer_pubkey: Option<BlsPublicKey>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builder_pubkey: Option<BlsPublicKey>,
+}
+
+impl DeliveredPayloadQuery {
+    pub fn by_slot(slot: u64) -> Self {
+        Self {
+            slot: Some(slot),
+            ..Default::default()
+        }
+    }
+
+    pub fn page(cursor: Option<u64>, limit: u64) -> Self {
+        Self {
+            cursor,
+            limit: Some(limit),
+            ..Default::default()
+        }
+    }
+}
+
+fn endpoint_path(endpoint: Endpoint) -> &'static str {
+    match endpoint {
+        Endpoint::BuilderBlocksReceived => BUILDER_BLOCKS_RECEIVED_PATH,
+        Endpoint::ProposerPayloadDelivered => PROPOSER_PAYLOAD_DELIVERED_PATH,
+        Endpoint::ValidatorRegistration => VALIDATOR_REGISTRATION_PATH,
+        Endpoint::BuilderStatus => BUILDER_STATUS_PATH,
+    }
+}
+
+// Spaces requests to a relay at least `interval` apart
+struct RateLimiter {
+    interval: Duration,
+    next_request: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_request: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn is_ready(&self) -> bool {
+        self.next_request
+            .try_lock()
+            .is_ok_and(|next_request| *next_request <= Instant::now())
+    }
+
+    // Reserves the next free request slot and waits for it
+    async fn acquire(&self) {
+        let at = {
+            let mut next_request = self.next_request.lock().await;
+            let at = (*next_request).max(Instant::now());
+            *next_request = at + self.interval;
+            at
+        };
+        time::sleep_until(at).await;
+    }
+}
+
+// Client for a single relay's Data API
+pub struct RelayClient {
+    pub relay_url: String,
+    pub config: RelayConfig,
+    http: Client,
+    rate_limiter: Option<RateLimiter>,
+    // Builder statuses by builder, with the time each was requested
+    builder_statuses: Mutex<HashMap<BlsPublicKey, (Instant, Option<BuilderStatus>)>>,
+}
+
+impl RelayClient {
+    // Panics if the HTTP client cannot be built; use `from_config` to handle that
+    pub fn new(relay_url: String) -> Self {
+        Self::from_config(RelayConfig::new(relay_url)).expect("Failed to build relay client")
+    }
+
+    pub fn from_config(config: RelayConfig) -> Result<Self, RelayConfigError> {
+        let http = Client::builder()
+            .timeout(config.request_timeout)
+            .default_headers(config.header_map()?)
+            .build()
+            .map_err(|err| RelayConfigError::HttpClient {
+                relay_url: config.url.clone(),
+                reason: err.to_string(),
+            })?;
+
+        Ok(Self {
+            relay_url: config.url.clone(),
+            rate_limiter: config.min_request_interval().map(RateLimiter::new),
+            config,
+            http,
+            builder_statuses: Mutex::new(HashMap::new()),
+        })
+    }
+
+    // Whether a request can be sent now without waiting on the relay's rate limit
+    pub fn is_ready(&self) -> bool {
+        self.rate_limiter
+            .as_ref()
+            .is_none_or(|rate_limiter| rate_limiter.is_ready())
+    }
+
+    pub fn is_enabled(&self, endpoint: Endpoint) -> bool {
+        self.config.is_enabled(endpoint)
+    }
+
+    // Fetches all bids the relay received for the given block number
+    pub async fn get_builder_bids(&self, block_num: u64) -> Result<BidResponse, RelayError> {
+        self.get_builder_blocks_received(&BidTraceQuery::by_block_number(block_num))
+            .await
+    }
+
+    // Fetches the bids the relay received that match `query`
+    pub async fn get_builder_blocks_received(
+        &self,
+        query: &BidTraceQuery,
+    ) -> Result<BidResponse, RelayError> {
+        let entries: Vec<Box<RawValue>> = self
+            .get_json(Endpoint::BuilderBlocksReceived, query)
+            .await?;
+
+        // One undecodable entry must not hide the relay's other bids
+        let mut bid_traces = Vec::with_capacity(entries.len());
+        let mut malformed = Vec::new();
+        for entry in entries {
+            match serde_json::from_str::<BidTrace>(entry.get()) {
+                Ok(bid) => bid_traces.push(bid),
+                Err(err) => malformed.push(MalformedBid {
+                    entry: serde_json::from_str(entry.get())
+                        .unwrap_or_else(|_| Value::String(entry.get().to_string())),
+                    error: err.to_string(),
+                }),
+            }
+        }
+
+        let kind = self.config.kind;
+        let statuses = self.builder_statuses_for(&bid_traces).await;
+        let details = bid_traces
+            .iter()
+            .map(|bid| {
+                let details = BidDetails::from_extra_fields(kind, &bid.extra);
+                let details = match statuses.get(&bid.builder_pubkey) {
+                    Some(status) => details.with_builder_status(status),
+                    None => details,
+                };
+                (bid.key(), details)
+            })
+            .collect();
+
+        Ok(BidResponse {
+            relay_url: self.relay_url.clone(),
+            relay_kind: kind,
+            bid_traces,
+            details,
+            malformed,
+        })
+    }
+
+    // Fetches one page of payloads the relay delivered to proposers that match `query`
+    pub async fn get_delivered_payloads(
+        &self,
+        query: &DeliveredPayloadQuery,
+    ) -> Result<DeliveredPayloadResponse, RelayError> {
+        let payloads: Vec<DeliveredPayload> = self
+            .get_json(Endpoint::ProposerPayloadDelivered, query)
+            .await?;
+        Ok(DeliveredPayloadResponse {
+            relay_url: self.relay_url.clone(),
+            payloads,
+        })
+    }
+
+    // Fetches the payload this relay delivered for `slot`, if it delivered one
+    pub async fn get_delivered_payload_for_slot(
+        &self,
+        slot: u64,
+    ) -> Result<Option<DeliveredPayload>, RelayError> {
+        let response = self
+            .get_delivered_payloads(&DeliveredPayloadQuery::by_slot(slot))
             .await?;
+        Ok(response
+            .payloads
+            .into_iter()
+            .find(|payload| payload.slot == U256::from(slot)))
+    }
+
+    // Fetches the latest registration `pubkey` submitted to this relay. Relays answer
+    // 404, or 400 with a "no registration" message, for validators they do not know.
+    pub async fn get_validator_registration(
+        &self,
+        pubkey: &BlsPublicKey,
+    ) -> Result<Option<SignedValidatorRegistration>, RelayError> {
+        match self
+            .get_json(
+                Endpoint::ValidatorRegistration,
+                &[("pubkey", pubkey.to_string())],
+            )
+            .await
+        {
+            Ok(registration) => Ok(Some(registration)),
+            Err(RelayError::Status { status, body, .. })
+                if status == StatusCode::NOT_FOUND
+                    || (status == StatusCode::BAD_REQUEST
+                        && body.to_lowercase().contains("no registration")) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    // Fetches `builder_pubkey`'s optimistic status and collateral from the relay's
+    // internal API. mev-boost-relay answers 400 "builder not found" for builders it
+    // does not know.
+    pub async fn get_builder_status(
+        &self,
+        builder_pubkey: &BlsPublicKey,
+    ) -> Result<Option<BuilderStatus>, RelayError> {
+        let path = format!("{}/{}", BUILDER_STATUS_PATH, builder_pubkey);
+        let no_query: [(&str, &str); 0] = [];
+        match self
+            .get_json_at(Endpoint::BuilderStatus, &path, &no_query)
+            .await
+        {
+            Ok(status) => Ok(Some(status)),
+            Err(RelayError::Status { status, body, .. })
+                if status == StatusCode::NOT_FOUND
+                    || (status == StatusCode::BAD_REQUEST
+                        && body.to_lowercase().contains("not found")) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
 
-    // Subscribe to new blocks
-    let mut block_stream = provider.subscribe_blocks().await?;
+    // Statuses of the builders of `bids` on an optimistic relay with the builder
+    // status endpoint enabled; empty otherwise. Each status is requested at most
+    // once per `BUILDER_STATUS_TTL`, and a failed request is not retried sooner, so
+    // the lookups do not crowd out bid polling.
+    async fn builder_statuses_for(
+        &self,
+        bids: &[BidTrace],
+    ) -> HashMap<BlsPublicKey, BuilderStatus> {
+        let mut statuses = HashMap::new();
+        if self.config.kind != RelayKind::Optimistic || !self.is_enabled(Endpoint::BuilderStatus) {
+            return statuses;
+        }
 
-    // Process new blocks as they come in
-    while let Some(block) = block_stream.next().await {
-        let block_number = block.number.expect("Block number not found in new block");
-        println!("New block: {}", block_number);
+        let builders: BTreeSet<_> = bids.iter().map(|bid| bid.builder_pubkey).collect();
+        for builder in builders {
+            let cached = self
+                .builder_statuses
+                .lock()
+                .await
+                .get(&builder)
+                .filter(|(requested_at, _)| requested_at.elapsed() < BUILDER_STATUS_TTL)
+                .map(|(_, status)| status.clone());
+            let status = match cached {
+                Some(status) => status,
+                None => {
+                    let status = self.get_builder_status(&builder).await.ok().flatten();
+                    self.builder_statuses
+                        .lock()
+                        .await
+                        .insert(builder, (Instant::now(), status.clone()));
+                    status
+                }
+            };
+            if let Some(status) = status {
+                statuses.insert(builder, status);
+            }
+        }
+        statuses
+    }
 
-        // Poll for each new block
-        relay_clients
-            .poll_for(block_number + U64::one(), 1, 12)
+    // Waits for the relay's rate limit, then requests `endpoint` with `query`
+    async fn get_json<Q, T>(&self, endpoint: Endpoint, query: &Q) -> Result<T, RelayError>
+    where
+        Q: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        self.get_json_at(endpoint, endpoint_path(endpoint), query)
             .await
     }
 
-    Ok(())
+    // Like `get_json`, for endpoints whose `path` carries a parameter
+    async fn get_json_at<Q, T>(
+        &self,
+        endpoint: Endpoint,
+        path: &str,
+        query: &Q,
+    ) -> Result<T, RelayError>
+    where
+        Q: Serialize + ?Sized,
+        T: DeserializeOwned,
+    {
+        if !self.is_enabled(endpoint) {
+            return Err(RelayError::EndpointDisabled(endpoint));
+        }
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+
+        let url = format!("{}{}", self.relay_url, path);
+        let response = self.http.get(&url).query(query).send().await?;
+
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after);
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(RelayError::Status {
+                status,
+                body,
+                retry_after,
+            });
+        }
+
+        Ok(serde_json::from_str(&body)?)
+    }
 }
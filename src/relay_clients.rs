@@ -1,53 +1,418 @@
-use std::{sync::Arc, time::Duration};
-use alloy_rs::types::U64;
-use tokio::{select, time};
+use alloy_primitives::{B256, U256};
+use futures::{future::join_all, StreamExt};
+use std::{
+    collections::BTreeSet,
+    fmt,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::{select, sync::RwLock, time};
 
-use crate::{bid_manager::BidManager, relay_client::RelayClient};
+use crate::{
+    beacon_events::{BeaconEvent, BeaconEventError, BeaconEventSource},
+    bid_manager::BidManager,
+    config::RelayEntry,
+    network::NetworkSpec,
+    relay_client::{BidTraceQuery, DeliveredPayloadQuery, RelayClient, RelayError},
+    relay_config::{Endpoint, RelayConfig, RelayConfigError},
+    relay_health::{HealthPolicy, RelayHealthTracker},
+    slot_clock::{PollSchedule, SlotClock, SlotOffset, SlotWindow},
+    types::{
+        BidEvent, BidResponse, BidTrace, BlsPublicKey, DeliveredPayload, RegistrationMismatch,
+        SignedValidatorRegistration,
+    },
+};
+
+// Relays added, removed and reconfigured by a reload, by relay URL
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelaySetChange {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub updated: Vec<String>,
+}
+
+impl RelaySetChange {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+impl fmt::Display for RelaySetChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "no relay changes");
+        }
+        let mut changes = Vec::new();
+        for (label, relays) in [
+            ("added", &self.added),
+            ("removed", &self.removed),
+            ("updated", &self.updated),
+        ] {
+            if !relays.is_empty() {
+                changes.push(format!("{} {}", label, relays.join(", ")));
+            }
+        }
+        write!(f, "{}", changes.join("; "))
+    }
+}
 
 pub struct RelayClients {
-    // All relay clients to read block builder bids from.
-    pub clients: Vec<Arc<RelayClient>>,
+    // All relay clients to read block builder bids from. The set can change while
+    // polling; every polling round reads the relays current at that time.
+    clients: RwLock<Vec<Arc<RelayClient>>>,
     // Bid manager to merge and sort bids.
     pub bid_manager: Arc<BidManager>,
+    // Failure tracking used to back off from and pause unhealthy relays.
+    pub health: Arc<RelayHealthTracker>,
 }
 
 impl RelayClients {
     pub fn new(relay_urls: Vec<String>) -> Self {
         Self {
-            clients: relay_urls
-                .into_iter()
-                .map(|r| Arc::new(RelayClient::new(r)))
-                .collect(),
+            clients: RwLock::new(
+                relay_urls
+                    .into_iter()
+                    .map(|r| Arc::new(RelayClient::new(r)))
+                    .collect(),
+            ),
             bid_manager: Arc::new(BidManager::new()),
+            health: Arc::new(RelayHealthTracker::default()),
+        }
+    }
+
+    // Builds a client per relay from its own timeout, rate limit and headers
+    pub fn from_configs(configs: Vec<RelayConfig>) -> Result<Self, RelayConfigError> {
+        let clients = configs
+            .into_iter()
+            .map(|config| RelayClient::from_config(config).map(Arc::new))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            clients: RwLock::new(clients),
+            bid_manager: Arc::new(BidManager::new()),
+            health: Arc::new(RelayHealthTracker::default()),
+        })
+    }
+
+    // Watches the default relays of `network`
+    pub fn for_network(network: &NetworkSpec) -> Result<Self, RelayConfigError> {
+        Self::from_configs(
+            network
+                .default_relays
+                .iter()
+                .map(RelayEntry::relay_config)
+                .collect(),
+        )
+    }
+
+    // Replaces the bid manager, e.g. one configured with a different retention
+    pub fn with_bid_manager(mut self, bid_manager: BidManager) -> Self {
+        self.bid_manager = Arc::new(bid_manager);
+        self
+    }
+
+    pub fn with_health_policy(mut self, policy: HealthPolicy) -> Self {
+        self.health = Arc::new(RelayHealthTracker::new(policy));
+        self
+    }
+
+    // The relays currently watched
+    pub async fn clients(&self) -> Vec<Arc<RelayClient>> {
+        self.clients.read().await.clone()
+    }
+
+    pub async fn relay_urls(&self) -> Vec<String> {
+        self.clients
+            .read()
+            .await
+            .iter()
+            .map(|client| client.relay_url.clone())
+            .collect()
+    }
+
+    // Starts watching a relay, or reconfigures it if its URL is already watched
+    pub async fn add_relay(&self, config: RelayConfig) -> Result<(), RelayConfigError> {
+        let mut clients = self.clients.write().await;
+        let mut configs: Vec<_> = clients.iter().map(|client| client.config.clone()).collect();
+        match configs.iter_mut().find(|c| c.url == config.url) {
+            Some(existing) => *existing = config,
+            None => configs.push(config),
+        }
+        let change = Self::reload_locked(&mut clients, configs)?;
+        drop(clients);
+
+        self.forget_changed(&change).await;
+        Ok(())
+    }
+
+    // Stops watching a relay. Returns whether it was watched.
+    pub async fn remove_relay(&self, relay_url: &str) -> bool {
+        let relay_url = relay_url.trim_end_matches('/');
+        let removed = {
+            let mut clients = self.clients.write().await;
+            let len = clients.len();
+            clients.retain(|client| client.relay_url != relay_url);
+            clients.len() != len
+        };
+        if removed {
+            self.health.forget(relay_url).await;
+        }
+        removed
+    }
+
+    // Replaces the watched relays with `configs` without touching the bid manager,
+    // so collected bids and subscriptions carry over. Relays whose config is
+    // unchanged keep their client; removed and reconfigured relays start over with
+    // a clean health record. If any config is invalid the relay set is left as is.
+    pub async fn reload_relays(
+        &self,
+        configs: Vec<RelayConfig>,
+    ) -> Result<RelaySetChange, RelayConfigError> {
+        let mut clients = self.clients.write().await;
+        let change = Self::reload_locked(&mut clients, configs)?;
+        drop(clients);
+
+        self.forget_changed(&change).await;
+        Ok(change)
+    }
+
+    // Replaces `clients` with clients for `configs`. Callers hold the write lock
+    // for the whole read-modify-write so concurrent changes are not lost.
+    fn reload_locked(
+        clients: &mut Vec<Arc<RelayClient>>,
+        configs: Vec<RelayConfig>,
+    ) -> Result<RelaySetChange, RelayConfigError> {
+        let mut change = RelaySetChange::default();
+        let mut reloaded: Vec<Arc<RelayClient>> = Vec::with_capacity(configs.len());
+
+        for config in configs {
+            // A later entry for the same relay replaces an earlier one
+            reloaded.retain(|client| client.relay_url != config.url);
+            let client = match clients.iter().find(|client| client.relay_url == config.url) {
+                Some(client) if client.config == config => client.clone(),
+                _ => Arc::new(RelayClient::from_config(config)?),
+            };
+            reloaded.push(client);
+        }
+
+        for client in reloaded.iter() {
+            match clients.iter().find(|c| c.relay_url == client.relay_url) {
+                Some(existing) if Arc::ptr_eq(existing, client) => {}
+                Some(_) => change.updated.push(client.relay_url.clone()),
+                None => change.added.push(client.relay_url.clone()),
+            }
+        }
+        change.removed = clients
+            .iter()
+            .filter(|client| !reloaded.iter().any(|c| c.relay_url == client.relay_url))
+            .map(|client| client.relay_url.clone())
+            .collect();
+
+        *clients = reloaded;
+        Ok(change)
+    }
+
+    // Removed and reconfigured relays start over with a clean health record
+    async fn forget_changed(&self, change: &RelaySetChange) {
+        for relay_url in change.removed.iter().chain(&change.updated) {
+            self.health.forget(relay_url).await;
+        }
+    }
+
+    // Polls for builder bids every `poll_interval` for `poll_for`. Every slot that
+    // received bids during the window is closed when polling ends.
+    pub async fn poll_for(&self, block_num: u64, poll_interval: Duration, poll_for: Duration) {
+        let deadline = time::Instant::now() + poll_for;
+        let polled_slots = self
+            .poll_until(
+                BidTraceQuery::by_block_number(block_num),
+                None,
+                &PollSchedule::fixed(poll_interval),
+                SystemTime::now(),
+                deadline,
+            )
+            .await;
+
+        for slot in polled_slots {
+            self.bid_manager.close_slot(slot).await;
+        }
+    }
+
+    // Polls for the bids of `slot` by slot number during its window, as often as
+    // `schedule` asks for at each point of the slot, then closes it
+    pub async fn poll_slot(
+        &self,
+        clock: &SlotClock,
+        slot: u64,
+        window: &SlotWindow,
+        schedule: &PollSchedule,
+    ) {
+        self.poll_slot_on_parent(clock, slot, None, window, schedule)
+            .await
+    }
+
+    // Like `poll_slot`, but when `parent_hash` is given only bids building on that
+    // execution block are kept; bids for a stale or reorged parent are dropped.
+    pub async fn poll_slot_on_parent(
+        &self,
+        clock: &SlotClock,
+        slot: u64,
+        parent_hash: Option<B256>,
+        window: &SlotWindow,
+        schedule: &PollSchedule,
+    ) {
+        time::sleep(clock.duration_until(window.start(clock, slot))).await;
+        let deadline = time::Instant::now() + clock.duration_until(window.end(clock, slot));
+        self.poll_until(
+            BidTraceQuery::by_slot(slot),
+            parent_hash,
+            schedule,
+            clock.slot_start(slot),
+            deadline,
+        )
+        .await;
+        self.bid_manager.close_slot(slot).await;
+    }
+
+    // Polls each upcoming slot announced by the beacon node's payload_attributes
+    // events, keyed on the parent hash the proposer will build on. Returns when the
+    // event stream ends or fails; events that cannot be decoded are skipped. Heads
+    // and watched slots are announced as status events.
+    pub async fn follow_beacon_events(
+        &self,
+        source: &BeaconEventSource,
+        clock: &SlotClock,
+        window: &SlotWindow,
+        schedule: &PollSchedule,
+    ) -> Result<(), BeaconEventError> {
+        let mut events = source.subscribe().await?;
+        let mut last_polled_slot = None;
+
+        while let Some(event) = events.next().await {
+            let event = match event {
+                Ok(event) => event,
+                // One malformed event says nothing about the ones after it
+                Err(BeaconEventError::Decode { event, source }) => {
+                    self.bid_manager
+                        .report_status(BidEvent::BeaconEventSkipped {
+                            beacon_event: event,
+                            error: source.to_string(),
+                        });
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            match event {
+                BeaconEvent::Head(head) => self
+                    .bid_manager
+                    .report_status(BidEvent::NewHead { slot: head.slot }),
+                BeaconEvent::PayloadAttributes(attributes) => {
+                    let data = attributes.data;
+                    // Events queued while the previous slot was polled may be stale
+                    if last_polled_slot.is_some_and(|last| data.proposal_slot <= last)
+                        || window.end(clock, data.proposal_slot) <= SystemTime::now()
+                    {
+                        continue;
+                    }
+                    self.bid_manager.report_status(BidEvent::SlotWatched {
+                        slot: data.proposal_slot,
+                        proposer_index: Some(data.proposer_index),
+                        parent_hash: Some(data.parent_block_hash),
+                    });
+                    self.poll_slot_on_parent(
+                        clock,
+                        data.proposal_slot,
+                        Some(data.parent_block_hash),
+                        window,
+                        schedule,
+                    )
+                    .await;
+                    last_polled_slot = Some(data.proposal_slot);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Follows the slot clock forever, polling every slot during its window. Each
+    // slot is announced with a `SlotWatched` event before it is polled.
+    pub async fn run(&self, clock: &SlotClock, window: &SlotWindow, schedule: &PollSchedule) {
+        loop {
+            let slot = window.next_slot(clock, SystemTime::now());
+            self.bid_manager.report_status(BidEvent::SlotWatched {
+                slot,
+                proposer_index: None,
+                parent_hash: None,
+            });
+            self.poll_slot(clock, slot, window, schedule).await;
         }
     }
 
-    // Polls for builder bids every `poll_interval_secs` second for `poll_for_secs` seconds.
-    pub async fn poll_for(&mut self, block_num: u64, poll_interval_secs: u64, poll_for_secs: u64) {
-        let poll_interval = Duration::from_secs(poll_interval_secs);
-        let mut interval_timer = time::interval(poll_interval);
-        let start_time = time::Instant::now();
-        let duration = Duration::from_secs(poll_for_secs);
+    // Polls every relay with `query` until `deadline`, waiting between rounds as long
+    // as `schedule` asks for at the current offset from `slot_start`, and returns the
+    // slots that received bids.
+    async fn poll_until(
+        &self,
+        query: BidTraceQuery,
+        parent_hash: Option<B256>,
+        schedule: &PollSchedule,
+        slot_start: SystemTime,
+        deadline: time::Instant,
+    ) -> BTreeSet<u64> {
+        let mut next_poll = time::Instant::now();
+        let mut polled_slots = BTreeSet::new();
 
         loop {
             select! {
-                _ = interval_timer.tick() => {
+                _ = time::sleep_until(next_poll) => {
                     // Check if the total polling duration has been exceeded
-                    if time::Instant::now().duration_since(start_time) > duration {
-                        self.bid_manager.clear_all().await;
+                    if time::Instant::now() >= deadline {
                         break;
                     }
+                    let offset = SlotOffset::between(slot_start, SystemTime::now());
+                    next_poll = time::Instant::now() + schedule.interval_at(offset);
 
                     let mut handles = Vec::new();
-                    for client in &self.clients {
-                        let client = client.clone();
+                    // Relays over their request budget sit this round out rather than
+                    // queueing requests that would arrive late
+                    let clients = self.clients().await.into_iter().filter(|client| {
+                        client.is_enabled(Endpoint::BuilderBlocksReceived) && client.is_ready()
+                    });
+                    for client in clients {
                         let bid_manager = self.bid_manager.clone();
-                        let block_num = block_num;
+                        let health = self.health.clone();
+                        let query = query.clone();
 
                         let handle = tokio::spawn(async move {
-                            if let Some(bid_response) = client.get_builder_bids(block_num).await {
-                                // Add bid traces to the bid manager
-                                bid_manager.add_bids(bid_response.bid_traces).await;
+                            // Skip relays that are backing off or paused
+                            if !health.is_available(&client.relay_url, query.slot).await {
+                                return Vec::new();
+                            }
+
+                            match client.get_builder_blocks_received(&query).await {
+                                Ok(mut bid_response) => {
+                                    health.record_success(&client.relay_url).await;
+                                    // Drop bids building on a different parent block
+                                    if let Some(parent_hash) = parent_hash {
+                                        bid_response
+                                            .bid_traces
+                                            .retain(|bid| bid.parent_hash == parent_hash);
+                                    }
+                                    let slots: Vec<u64> = bid_response
+                                        .bid_traces
+                                        .iter()
+                                        .map(|bid| bid.slot.saturating_to::<u64>())
+                                        .collect();
+                                    // Add bid traces to the bid manager
+                                    bid_manager.add_bid_response(bid_response).await;
+                                    slots
+                                }
+                                Err(err) => {
+                                    health.record_failure(&client.relay_url, &err, query.slot).await;
+                                    bid_manager.report_relay_error(&client.relay_url, query.slot, &err);
+                                    Vec::new()
+                                }
                             }
                         });
 
@@ -56,15 +421,168 @@ impl RelayClients {
 
                     // Await all handles to ensure all bid traces are inserted before the next interval
                     for handle in handles {
-                        let _ = handle.await;
+                        if let Ok(slots) = handle.await {
+                            polled_slots.extend(slots);
+                        }
                     }
                 }
-                // After poll_for_secs has elapsed, exit the loop
-                _ = time::sleep(duration) => {
-                    self.bid_manager.clear_all().await;
+                // Once the deadline has passed, exit the loop. Old slots are pruned by
+                // the bid manager's retention window, so in-flight slots are kept.
+                _ = time::sleep_until(deadline) => {
                     break;
                 }
             }
         }
+
+        polled_slots
+    }
+
+    // Queries every relay once with `query`, keyed by relay URL
+    pub async fn fetch_bids(
+        &self,
+        query: &BidTraceQuery,
+    ) -> Vec<(String, Result<BidResponse, RelayError>)> {
+        let clients: Vec<_> = self
+            .clients()
+            .await
+            .into_iter()
+            .filter(|client| client.is_enabled(Endpoint::BuilderBlocksReceived))
+            .collect();
+        let results = join_all(
+            clients
+                .iter()
+                .map(|client| client.get_builder_blocks_received(query)),
+        )
+        .await;
+
+        clients
+            .iter()
+            .map(|client| client.relay_url.clone())
+            .zip(results)
+            .collect()
+    }
+
+    // Asks every relay which payload it delivered for `slot`. Relays that shared the
+    // winning bid all report the same block, so the first payload found is returned.
+    pub async fn get_delivered_payload(&self, slot: u64) -> Option<DeliveredPayload> {
+        self.get_delivered_payloads_for_slot(slot)
+            .await
+            .into_iter()
+            .find_map(|(_, result)| result.ok().flatten())
+    }
+
+    // The payload each relay delivered for `slot`, keyed by relay URL
+    pub async fn get_delivered_payloads_for_slot(
+        &self,
+        slot: u64,
+    ) -> Vec<(String, Result<Option<DeliveredPayload>, RelayError>)> {
+        let clients: Vec<_> = self
+            .clients()
+            .await
+            .into_iter()
+            .filter(|client| client.is_enabled(Endpoint::ProposerPayloadDelivered))
+            .collect();
+        let results = join_all(
+            clients
+                .iter()
+                .map(|client| client.get_delivered_payload_for_slot(slot)),
+        )
+        .await;
+
+        clients
+            .iter()
+            .map(|client| client.relay_url.clone())
+            .zip(results)
+            .collect()
+    }
+
+    // Sends one small request to every relay and returns how long each took to
+    // answer. Outcomes are recorded in the relay health tracker. Relays reject
+    // unfiltered bid queries, so bids are asked for by the current slot on `clock`.
+    pub async fn check_relays(
+        &self,
+        clock: &SlotClock,
+    ) -> Vec<(String, Result<Duration, RelayError>)> {
+        let clients = self.clients().await;
+        let bid_query = BidTraceQuery::by_slot(clock.current_slot().unwrap_or_default());
+        let bid_query = &bid_query;
+        let probes = clients.iter().map(|client| async move {
+            let started = time::Instant::now();
+            let result = if client.is_enabled(Endpoint::ProposerPayloadDelivered) {
+                client
+                    .get_delivered_payloads(&DeliveredPayloadQuery::page(None, 1))
+                    .await
+                    .map(|_| ())
+            } else {
+                client
+                    .get_builder_blocks_received(bid_query)
+                    .await
+                    .map(|_| ())
+            };
+            match &result {
+                Ok(()) => self.health.record_success(&client.relay_url).await,
+                Err(err) => {
+                    self.health
+                        .record_failure(&client.relay_url, err, None)
+                        .await
+                }
+            }
+            (client.relay_url.clone(), result.map(|()| started.elapsed()))
+        });
+
+        join_all(probes).await
+    }
+
+    // Looks up the registration of `pubkey` at every relay serving registrations,
+    // keyed by relay URL
+    pub async fn get_validator_registrations(
+        &self,
+        pubkey: &BlsPublicKey,
+    ) -> Vec<(
+        String,
+        Result<Option<SignedValidatorRegistration>, RelayError>,
+    )> {
+        let clients: Vec<_> = self
+            .clients()
+            .await
+            .into_iter()
+            .filter(|client| client.is_enabled(Endpoint::ValidatorRegistration))
+            .collect();
+        let results = join_all(
+            clients
+                .iter()
+                .map(|client| client.get_validator_registration(pubkey)),
+        )
+        .await;
+
+        clients
+            .iter()
+            .map(|client| client.relay_url.clone())
+            .zip(results)
+            .collect()
+    }
+
+    // Checks `bids`, built on a parent block with `parent_gas_limit`, against the
+    // proposer's registration at each relay and returns the bids whose fee recipient
+    // or gas limit differ from what the proposer registered.
+    pub async fn flag_registration_mismatches(
+        &self,
+        proposer_pubkey: &BlsPublicKey,
+        parent_gas_limit: U256,
+        bids: &[BidTrace],
+    ) -> Vec<(String, BidTrace, Vec<RegistrationMismatch>)> {
+        let mut flagged = Vec::new();
+        for (relay_url, result) in self.get_validator_registrations(proposer_pubkey).await {
+            let Ok(Some(registration)) = result else {
+                continue;
+            };
+            for bid in bids {
+                let mismatches = registration.message.mismatches(bid, parent_gas_limit);
+                if !mismatches.is_empty() {
+                    flagged.push((relay_url.clone(), bid.clone(), mismatches));
+                }
+            }
+        }
+        flagged
     }
 }
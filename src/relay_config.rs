@@ -0,0 +1,183 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    fmt,
+    time::Duration,
+};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use serde::{Deserialize, Serialize};
+
+// Default timeout for a single request to a relay
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Relay endpoints a relay may be queried on
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Endpoint {
+    BuilderBlocksReceived,
+    ProposerPayloadDelivered,
+    ValidatorRegistration,
+    // mev-boost-relay's internal builder status API, with a builder's optimistic
+    // status and collateral. Relays usually expose it to their operators only.
+    BuilderStatus,
+}
+
+impl Endpoint {
+    // The public Data API endpoints, queried unless a relay is configured otherwise
+    pub const DATA_API: [Endpoint; 3] = [
+        Endpoint::BuilderBlocksReceived,
+        Endpoint::ProposerPayloadDelivered,
+        Endpoint::ValidatorRegistration,
+    ];
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::BuilderBlocksReceived => write!(f, "builder_blocks_received"),
+            Endpoint::ProposerPayloadDelivered => write!(f, "proposer_payload_delivered"),
+            Endpoint::ValidatorRegistration => write!(f, "validator_registration"),
+            Endpoint::BuilderStatus => write!(f, "builder_status"),
+        }
+    }
+}
+
+// How a relay handles builder submissions
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum RelayKind {
+    // Simulates every block before making its bid available
+    #[default]
+    Standard,
+    // Makes bids available before simulation, backed by builder collateral
+    Optimistic,
+    // Auctions part of the block space separately from the execution payload
+    Xga,
+}
+
+impl fmt::Display for RelayKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayKind::Standard => write!(f, "standard"),
+            RelayKind::Optimistic => write!(f, "optimistic"),
+            RelayKind::Xga => write!(f, "xga"),
+        }
+    }
+}
+
+// Errors in a relay configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayConfigError {
+    // A custom header has a name or value that cannot be sent over HTTP
+    InvalidHeader { relay_url: String, name: String },
+    // The HTTP client could not be set up, e.g. because no TLS backend is available
+    HttpClient { relay_url: String, reason: String },
+}
+
+impl fmt::Display for RelayConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayConfigError::InvalidHeader { relay_url, name } => {
+                write!(f, "invalid header {} for relay {}", name, relay_url)
+            }
+            RelayConfigError::HttpClient { relay_url, reason } => {
+                write!(
+                    f,
+                    "failed to build HTTP client for relay {}: {}",
+                    relay_url, reason
+                )
+            }
+        }
+    }
+}
+
+impl Error for RelayConfigError {}
+
+// Connection settings for a single relay. Relays rate-limit their Data API
+// differently, so each one gets its own timeout and request budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelayConfig {
+    pub url: String,
+    pub kind: RelayKind,
+    pub request_timeout: Duration,
+    // Requests per second the relay accepts; `None` means unlimited
+    pub max_requests_per_second: Option<u32>,
+    // Extra headers sent with every request, e.g. API keys
+    pub headers: BTreeMap<String, String>,
+    // Endpoints the relay is queried on; others are never requested
+    pub endpoints: BTreeSet<Endpoint>,
+}
+
+impl RelayConfig {
+    pub fn new(url: String) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            kind: RelayKind::default(),
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            max_requests_per_second: None,
+            headers: BTreeMap::new(),
+            endpoints: Endpoint::DATA_API.into_iter().collect(),
+        }
+    }
+
+    pub fn with_kind(mut self, kind: RelayKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn with_max_requests_per_second(mut self, max_requests_per_second: u32) -> Self {
+        self.max_requests_per_second = Some(max_requests_per_second);
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    // Sends `api_key` as the Authorization header
+    pub fn with_api_key(self, api_key: &str) -> Self {
+        self.with_header(AUTHORIZATION.as_str(), api_key)
+    }
+
+    pub fn with_endpoints(mut self, endpoints: impl IntoIterator<Item = Endpoint>) -> Self {
+        self.endpoints = endpoints.into_iter().collect();
+        self
+    }
+
+    pub fn is_enabled(&self, endpoint: Endpoint) -> bool {
+        self.endpoints.contains(&endpoint)
+    }
+
+    // Minimum spacing between two requests, if the relay is rate limited
+    pub fn min_request_interval(&self) -> Option<Duration> {
+        self.max_requests_per_second
+            .map(|rps| Duration::from_secs(1) / rps.max(1))
+    }
+
+    pub fn header_map(&self) -> Result<HeaderMap, RelayConfigError> {
+        let invalid_header = |name: &str| RelayConfigError::InvalidHeader {
+            relay_url: self.url.clone(),
+            name: name.to_string(),
+        };
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let header_name =
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid_header(name))?;
+            let mut header_value =
+                HeaderValue::from_str(value).map_err(|_| invalid_header(name))?;
+            header_value.set_sensitive(true);
+            headers.insert(header_name, header_value);
+        }
+        Ok(headers)
+    }
+}
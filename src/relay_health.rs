@@ -0,0 +1,232 @@
+use std::{collections::HashMap, fmt, time::Duration};
+
+use tokio::{sync::RwLock, time::Instant};
+
+use crate::relay_client::RelayError;
+
+// Controls how failing relays are throttled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthPolicy {
+    // Backoff after the first failure, doubled for every further consecutive failure
+    pub base_backoff: Duration,
+    pub max_backoff: Duration,
+    // Consecutive failures after which the circuit breaker pauses the relay
+    pub failure_threshold: u32,
+    // Number of slots a tripped relay is skipped for
+    pub pause_slots: u64,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self {
+            base_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(12),
+            failure_threshold: 5,
+            pause_slots: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayStatus {
+    Healthy,
+    // Recent requests failed; the relay is retried once its backoff expires
+    Degraded,
+    // The circuit breaker tripped; the relay is skipped until the given slot has passed
+    Paused { through_slot: u64 },
+    // The pause has passed; the relay is probed again and healthy after a success
+    Recovering,
+}
+
+impl fmt::Display for RelayStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayStatus::Healthy => write!(f, "healthy"),
+            RelayStatus::Degraded => write!(f, "degraded"),
+            RelayStatus::Paused { through_slot } => {
+                write!(f, "paused through slot {}", through_slot)
+            }
+            RelayStatus::Recovering => write!(f, "recovering"),
+        }
+    }
+}
+
+// Request outcomes of a single relay
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RelayHealth {
+    pub consecutive_failures: u32,
+    pub total_requests: u64,
+    pub total_failures: u64,
+    pub last_error: Option<String>,
+    // No request is sent before this instant
+    pub backoff_until: Option<Instant>,
+    // Set when the circuit breaker trips; the relay is skipped through this slot
+    pub paused_through_slot: Option<u64>,
+}
+
+impl RelayHealth {
+    // Status as of `current_slot`. A pause only ends once a success clears it, so
+    // after its last slot the relay is reported as recovering. Without a current slot
+    // a pause is assumed to still hold.
+    pub fn status(&self, current_slot: Option<u64>) -> RelayStatus {
+        match self.paused_through_slot {
+            Some(through_slot) if current_slot.is_none_or(|slot| slot <= through_slot) => {
+                RelayStatus::Paused { through_slot }
+            }
+            Some(_) => RelayStatus::Recovering,
+            None if self.consecutive_failures > 0 => RelayStatus::Degraded,
+            None => RelayStatus::Healthy,
+        }
+    }
+
+    // Whether a request for `slot` may be sent at `now`. Polling by block number has
+    // no slot, so a paused relay is then only held back by its backoff.
+    pub fn is_available(&self, slot: Option<u64>, now: Instant) -> bool {
+        let paused = matches!(
+            (self.paused_through_slot, slot),
+            (Some(through_slot), Some(slot)) if slot <= through_slot
+        );
+        !paused && self.backoff_until.is_none_or(|until| now >= until)
+    }
+
+    pub fn record_success(&mut self) {
+        self.total_requests += 1;
+        self.consecutive_failures = 0;
+        self.backoff_until = None;
+        self.paused_through_slot = None;
+    }
+
+    // Backs the relay off exponentially, or for as long as its `Retry-After` header
+    // asks if that is longer. Errors that retrying cannot fix are counted but do not
+    // throttle the relay.
+    pub fn record_failure(
+        &mut self,
+        error: &RelayError,
+        slot: Option<u64>,
+        now: Instant,
+        policy: &HealthPolicy,
+    ) {
+        self.total_requests += 1;
+        self.total_failures += 1;
+        self.last_error = Some(error.to_string());
+        if !error.is_retryable() {
+            return;
+        }
+
+        self.consecutive_failures += 1;
+        let exponent = (self.consecutive_failures - 1).min(16);
+        let backoff = policy
+            .base_backoff
+            .saturating_mul(1 << exponent)
+            .min(policy.max_backoff)
+            .max(error.retry_after().unwrap_or_default());
+        self.backoff_until = Some(now + backoff);
+
+        if self.consecutive_failures >= policy.failure_threshold {
+            if let Some(slot) = slot {
+                self.paused_through_slot = Some(slot + policy.pause_slots);
+            }
+        }
+    }
+}
+
+impl fmt::Display for RelayHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} requests failed, {} in a row",
+            self.total_failures, self.total_requests, self.consecutive_failures
+        )?;
+        if let Some(error) = &self.last_error {
+            write!(f, ", last error: {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+// Health of every relay, keyed by relay URL
+#[derive(Debug, Default)]
+pub struct RelayHealthTracker {
+    relays: RwLock<HashMap<String, RelayHealth>>,
+    policy: HealthPolicy,
+}
+
+impl RelayHealthTracker {
+    pub fn new(policy: HealthPolicy) -> Self {
+        Self {
+            relays: RwLock::new(HashMap::new()),
+            policy,
+        }
+    }
+
+    pub fn policy(&self) -> &HealthPolicy {
+        &self.policy
+    }
+
+    pub async fn is_available(&self, relay_url: &str, slot: Option<u64>) -> bool {
+        self.relays
+            .read()
+            .await
+            .get(relay_url)
+            .is_none_or(|health| health.is_available(slot, Instant::now()))
+    }
+
+    pub async fn record_success(&self, relay_url: &str) {
+        self.relays
+            .write()
+            .await
+            .entry(relay_url.to_string())
+            .or_default()
+            .record_success();
+    }
+
+    pub async fn record_failure(&self, relay_url: &str, error: &RelayError, slot: Option<u64>) {
+        self.relays
+            .write()
+            .await
+            .entry(relay_url.to_string())
+            .or_default()
+            .record_failure(error, slot, Instant::now(), &self.policy);
+    }
+
+    // Drops the record of a relay, e.g. one no longer watched
+    pub async fn forget(&self, relay_url: &str) {
+        self.relays.write().await.remove(relay_url);
+    }
+
+    pub async fn get(&self, relay_url: &str) -> RelayHealth {
+        self.relays
+            .read()
+            .await
+            .get(relay_url)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    // Health of every relay that has been queried, sorted by relay URL
+    pub async fn snapshot(&self) -> Vec<(String, RelayHealth)> {
+        let mut relays: Vec<_> = self
+            .relays
+            .read()
+            .await
+            .iter()
+            .map(|(relay_url, health)| (relay_url.clone(), health.clone()))
+            .collect();
+        relays.sort_by(|a, b| a.0.cmp(&b.0));
+        relays
+    }
+
+    // Relays that are backing off, paused or recovering as of `current_slot`, with
+    // their status
+    pub async fn degraded(
+        &self,
+        current_slot: Option<u64>,
+    ) -> Vec<(String, RelayStatus, RelayHealth)> {
+        self.snapshot()
+            .await
+            .into_iter()
+            .map(|(relay_url, health)| (relay_url, health.status(current_slot), health))
+            .filter(|(_, status, _)| *status != RelayStatus::Healthy)
+            .collect()
+    }
+}
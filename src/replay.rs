@@ -0,0 +1,109 @@
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{bid_manager::BidManager, types::BidTrace};
+
+// Errors returned while reading a replay file
+#[derive(Debug)]
+pub enum ReplayError {
+    // The file could not be read
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    // A line carries a bid that cannot be decoded
+    Parse {
+        line: usize,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io { path, source } => {
+                write!(f, "failed to read {}: {}", path.display(), source)
+            }
+            ReplayError::Parse { line, source } => {
+                write!(f, "invalid replay record on line {}: {}", line, source)
+            }
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReplayError::Io { source, .. } => Some(source),
+            ReplayError::Parse { source, .. } => Some(source),
+        }
+    }
+}
+
+// A bid reported by a relay. Bid events written as JSON lines by the watcher have
+// this shape, so its output can be replayed as is.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReplayRecord {
+    pub relay: String,
+    pub bid: BidTrace,
+}
+
+// Parses one JSON object per line. Lines that are not JSON, such as log lines
+// captured along with the output, and objects without a `relay` and a `bid`, such
+// as slot closed or relay error events, are skipped. A bid that cannot be decoded
+// is an error.
+pub fn parse_replay(contents: &str) -> Result<Vec<ReplayRecord>, ReplayError> {
+    let mut records = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if value.get("relay").is_none() || value.get("bid").is_none() {
+            continue;
+        }
+        // Decoded from the line rather than `value`, which would round large numbers
+        let record = serde_json::from_str(line).map_err(|source| ReplayError::Parse {
+            line: index + 1,
+            source,
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+pub async fn read_replay_file(path: impl AsRef<Path>) -> Result<Vec<ReplayRecord>, ReplayError> {
+    let path = path.as_ref();
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|source| ReplayError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    parse_replay(&contents)
+}
+
+// Feeds `records` to `bid_manager` in order, as if the relays had just reported
+// them. A slot is closed once a bid for a later slot arrives, and every slot still
+// open is closed at the end.
+pub async fn replay(bid_manager: &BidManager, records: Vec<ReplayRecord>) {
+    let mut open_slots = BTreeSet::new();
+    for record in records {
+        let slot = record.bid.slot.saturating_to::<u64>();
+        let still_open = open_slots.split_off(&slot);
+        for closed in std::mem::replace(&mut open_slots, still_open) {
+            bid_manager.close_slot(closed).await;
+        }
+        open_slots.insert(slot);
+        bid_manager.add_bids(&record.relay, vec![record.bid]).await;
+    }
+    for slot in open_slots {
+        bid_manager.close_slot(slot).await;
+    }
+}
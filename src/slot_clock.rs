@@ -0,0 +1,210 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// Beacon chain genesis times, in seconds since the Unix epoch
+pub const MAINNET_GENESIS_TIME: u64 = 1606824023;
+pub const SEPOLIA_GENESIS_TIME: u64 = 1655733600;
+pub const HOLESKY_GENESIS_TIME: u64 = 1695902400;
+
+pub const DEFAULT_SECONDS_PER_SLOT: u64 = 12;
+
+// Lower bound for any poll interval, so a misconfigured schedule cannot spin
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// Maps wall-clock time to beacon chain slots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotClock {
+    genesis_time: u64,
+    seconds_per_slot: u64,
+}
+
+impl SlotClock {
+    pub fn new(genesis_time: u64, seconds_per_slot: u64) -> Self {
+        Self {
+            genesis_time,
+            seconds_per_slot: seconds_per_slot.max(1),
+        }
+    }
+
+    pub fn mainnet() -> Self {
+        Self::new(MAINNET_GENESIS_TIME, DEFAULT_SECONDS_PER_SLOT)
+    }
+
+    pub fn sepolia() -> Self {
+        Self::new(SEPOLIA_GENESIS_TIME, DEFAULT_SECONDS_PER_SLOT)
+    }
+
+    pub fn holesky() -> Self {
+        Self::new(HOLESKY_GENESIS_TIME, DEFAULT_SECONDS_PER_SLOT)
+    }
+
+    pub fn genesis_time(&self) -> u64 {
+        self.genesis_time
+    }
+
+    pub fn slot_duration(&self) -> Duration {
+        Duration::from_secs(self.seconds_per_slot)
+    }
+
+    // Slot in progress at `time`, or `None` before genesis
+    pub fn slot_at(&self, time: SystemTime) -> Option<u64> {
+        let since_epoch = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let since_genesis = since_epoch.checked_sub(self.genesis_time)?;
+        Some(since_genesis / self.seconds_per_slot)
+    }
+
+    // Slot in progress now, or `None` before genesis
+    pub fn current_slot(&self) -> Option<u64> {
+        self.slot_at(SystemTime::now())
+    }
+
+    // Unix timestamp at which `slot` starts; also the timestamp of its execution payload
+    pub fn slot_timestamp(&self, slot: u64) -> u64 {
+        self.genesis_time + slot * self.seconds_per_slot
+    }
+
+    pub fn slot_start(&self, slot: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.slot_timestamp(slot))
+    }
+
+    // Time left until `time`, or zero if it has passed
+    pub fn duration_until(&self, time: SystemTime) -> Duration {
+        time.duration_since(SystemTime::now()).unwrap_or_default()
+    }
+}
+
+// Portion of a slot during which relays are polled for its bids, relative to the
+// slot start: polling begins `lead` before the slot boundary and stops at `deadline`
+// after it, when the proposer has requested a header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotWindow {
+    pub lead: Duration,
+    pub deadline: Duration,
+}
+
+impl Default for SlotWindow {
+    fn default() -> Self {
+        Self {
+            lead: Duration::from_secs(8),
+            deadline: Duration::from_secs(1),
+        }
+    }
+}
+
+impl SlotWindow {
+    pub fn start(&self, clock: &SlotClock, slot: u64) -> SystemTime {
+        clock
+            .slot_start(slot)
+            .checked_sub(self.lead)
+            .unwrap_or(UNIX_EPOCH)
+    }
+
+    pub fn end(&self, clock: &SlotClock, slot: u64) -> SystemTime {
+        clock.slot_start(slot) + self.deadline
+    }
+
+    // First slot whose window has not ended at `time`
+    pub fn next_slot(&self, clock: &SlotClock, time: SystemTime) -> u64 {
+        let slot = clock.slot_at(time).unwrap_or(0);
+        if self.end(clock, slot) > time {
+            slot
+        } else {
+            slot + 1
+        }
+    }
+}
+
+// Signed offset from the start of a slot, with millisecond precision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct SlotOffset(i64);
+
+impl SlotOffset {
+    pub const SLOT_START: SlotOffset = SlotOffset(0);
+
+    pub fn before(duration: Duration) -> Self {
+        Self(-(duration.as_millis() as i64))
+    }
+
+    pub fn after(duration: Duration) -> Self {
+        Self(duration.as_millis() as i64)
+    }
+
+    // Offset of `time` from `slot_start`
+    pub fn between(slot_start: SystemTime, time: SystemTime) -> Self {
+        match time.duration_since(slot_start) {
+            Ok(after) => Self::after(after),
+            Err(err) => Self::before(err.duration()),
+        }
+    }
+
+    pub fn as_millis(&self) -> i64 {
+        self.0
+    }
+}
+
+// Poll every `interval` from `from` until the next phase starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollPhase {
+    pub from: SlotOffset,
+    pub interval: Duration,
+}
+
+// How often relays are polled as a slot progresses. Bidding is concentrated around
+// the slot boundary, so the default schedule polls sparsely early on and quickly
+// from two seconds before the slot until the proposer requests a header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PollSchedule {
+    phases: Vec<PollPhase>,
+}
+
+impl Default for PollSchedule {
+    fn default() -> Self {
+        Self::new(vec![
+            PollPhase {
+                from: SlotOffset::before(Duration::from_secs(8)),
+                interval: Duration::from_secs(1),
+            },
+            PollPhase {
+                from: SlotOffset::before(Duration::from_secs(2)),
+                interval: Duration::from_millis(250),
+            },
+            PollPhase {
+                from: SlotOffset::SLOT_START,
+                interval: Duration::from_millis(100),
+            },
+        ])
+    }
+}
+
+impl PollSchedule {
+    // Phases may be given in any order; intervals are clamped to `MIN_POLL_INTERVAL`
+    pub fn new(mut phases: Vec<PollPhase>) -> Self {
+        for phase in &mut phases {
+            phase.interval = phase.interval.max(MIN_POLL_INTERVAL);
+        }
+        phases.sort_by_key(|phase| phase.from);
+        Self { phases }
+    }
+
+    // The same interval throughout the slot
+    pub fn fixed(interval: Duration) -> Self {
+        Self::new(vec![PollPhase {
+            from: SlotOffset::default(),
+            interval,
+        }])
+    }
+
+    pub fn phases(&self) -> &[PollPhase] {
+        &self.phases
+    }
+
+    // Interval of the phase in progress at `offset`. Before the first phase its
+    // interval applies; an empty schedule polls once a second.
+    pub fn interval_at(&self, offset: SlotOffset) -> Duration {
+        self.phases
+            .iter()
+            .rev()
+            .find(|phase| phase.from <= offset)
+            .or(self.phases.first())
+            .map_or(Duration::from_secs(1), |phase| phase.interval)
+    }
+}
@@ -1,60 +1,689 @@
-/// Imports the `Address` and `U256` types from the `alloy_rs::types` module.
-/// These types are likely used throughout the codebase to represent Ethereum addresses
-/// and 256-bit unsigned integers, respectively.
-use alloy_rs::types::{Address, U256};
-use serde::{Deserialize, Deserializer, Serialize};
+/// Imports the `Address`, `B256` and `U256` types from `alloy_primitives`.
+/// These types are used throughout the codebase to represent Ethereum addresses,
+/// 32-byte hashes and 256-bit unsigned integers, respectively.
+use alloy_primitives::{hex, Address, FixedBytes, B256, U256};
+use serde::{
+    de::{self, MapAccess, Unexpected, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
 /// types
 ///
 ///
 ///
 ///
 use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    error::Error,
     fmt,
     hash::{Hash, Hasher},
+    str::FromStr,
 };
 
-// Define a custom deserialization function for U256 from string
-fn deserialize_u256_from_string<'de, D>(deserializer: D) -> Result<U256, D::Error>
+use serde_json::{value::RawValue, Value};
+
+use crate::{relay_config::RelayKind, slot_clock::SlotClock};
+
+// Parses a decimal or 0x-prefixed hex integer. `from_str_radix` alone would also
+// take an empty string or `_` separators.
+fn parse_u256(s: &str) -> Option<U256> {
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => (hex, 16),
+        None => (s, 10),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    U256::from_str_radix(digits, radix as u64).ok()
+}
+
+// A U256 as relays encode it: the Data API specifies decimal strings, but some
+// relays send JSON numbers or hex strings instead. The value is read from its raw
+// JSON text, so numbers above u64 keep their exact digits.
+struct TolerantU256(U256);
+
+impl<'de> Deserialize<'de> for TolerantU256 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = Box::<RawValue>::deserialize(deserializer)?;
+        let text = raw.get().trim();
+        let value = if text.starts_with('"') {
+            serde_json::from_str::<String>(text)
+                .ok()
+                .and_then(|s| parse_u256(&s))
+        } else {
+            parse_u256(text)
+        };
+        value.map(TolerantU256).ok_or_else(|| {
+            de::Error::invalid_value(
+                Unexpected::Other(text),
+                &"an unsigned integer as a number, decimal string or hex string",
+            )
+        })
+    }
+}
+
+// Deserialize U256 from a decimal string, hex string or number
+pub(crate) fn deserialize_u256<'de, D>(deserializer: D) -> Result<U256, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s = String::deserialize(deserializer)?;
-    U256::from_dec_str(&s).map_err(serde::de::Error::custom)
+    TolerantU256::deserialize(deserializer).map(|value| value.0)
 }
 
-// Define the BidTrace struct
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+// Serialize U256 as a decimal string, matching the relay Data API format
+pub(crate) fn serialize_u256_as_string<S>(value: &U256, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&value.to_string())
+}
+
+// Length of a compressed BLS12-381 public key in bytes
+pub const BLS_PUBLIC_KEY_LENGTH: usize = 48;
+
+// Why a string is not a valid BLS public key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlsPublicKeyError {
+    InvalidHex(String),
+    InvalidLength(usize),
+}
+
+impl fmt::Display for BlsPublicKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlsPublicKeyError::InvalidHex(reason) => {
+                write!(f, "invalid BLS public key hex: {}", reason)
+            }
+            BlsPublicKeyError::InvalidLength(length) => write!(
+                f,
+                "BLS public key must be {} bytes, got {}",
+                BLS_PUBLIC_KEY_LENGTH, length
+            ),
+        }
+    }
+}
+
+impl Error for BlsPublicKeyError {}
+
+// A builder or proposer BLS public key. Relays send it as 0x-prefixed hex in
+// whatever case they like; two keys are equal when their bytes are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct BlsPublicKey(FixedBytes<BLS_PUBLIC_KEY_LENGTH>);
+
+impl BlsPublicKey {
+    pub const fn new(bytes: [u8; BLS_PUBLIC_KEY_LENGTH]) -> Self {
+        Self(FixedBytes(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; BLS_PUBLIC_KEY_LENGTH] {
+        &self.0 .0
+    }
+}
+
+impl FromStr for BlsPublicKey {
+    type Err = BlsPublicKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|err| BlsPublicKeyError::InvalidHex(err.to_string()))?;
+        let bytes: [u8; BLS_PUBLIC_KEY_LENGTH] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| BlsPublicKeyError::InvalidLength(bytes.len()))?;
+        Ok(Self::new(bytes))
+    }
+}
+
+// Lowercase 0x-prefixed hex, as relays expect it in queries
+impl fmt::Display for BlsPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for BlsPublicKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for BlsPublicKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// Highest bid value accepted as plausible: 10,000 ETH in wei
+pub const MAX_BID_VALUE: U256 = U256::from_limbs([1864712049423024128, 542, 0, 0]);
+
+// An invariant a bid trace breaks. Relays occasionally report such bids; they are
+// quarantined rather than merged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BidValidationError {
+    ZeroSlot,
+    ZeroGasLimit,
+    GasUsedAboveLimit { gas_used: U256, gas_limit: U256 },
+    // `timestamp_ms` does not fall within the second given by `timestamp`
+    TimestampMismatch { timestamp: U256, timestamp_ms: U256 },
+    // The bid was submitted more than one slot away from the start of its slot
+    TimestampOutsideSlot { slot: U256, timestamp: U256 },
+    ValueTooHigh { value: U256 },
+    // The slot lies past the slot clock's current slot, or without a clock, far past
+    // the newest slot accepted so far
+    SlotTooFarAhead { slot: U256, reference_slot: u64 },
+}
+
+impl fmt::Display for BidValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BidValidationError::ZeroSlot => write!(f, "slot is zero"),
+            BidValidationError::ZeroGasLimit => write!(f, "gas limit is zero"),
+            BidValidationError::GasUsedAboveLimit {
+                gas_used,
+                gas_limit,
+            } => write!(f, "gas used {} exceeds gas limit {}", gas_used, gas_limit),
+            BidValidationError::TimestampMismatch {
+                timestamp,
+                timestamp_ms,
+            } => write!(
+                f,
+                "timestamp_ms {} does not match timestamp {}",
+                timestamp_ms, timestamp
+            ),
+            BidValidationError::TimestampOutsideSlot { slot, timestamp } => {
+                write!(f, "timestamp {} is too far from slot {}", timestamp, slot)
+            }
+            BidValidationError::ValueTooHigh { value } => {
+                write!(f, "value {} wei is above {} wei", value, MAX_BID_VALUE)
+            }
+            BidValidationError::SlotTooFarAhead {
+                slot,
+                reference_slot,
+            } => write!(
+                f,
+                "slot {} is too far ahead of slot {}",
+                slot, reference_slot
+            ),
+        }
+    }
+}
+
+impl Error for BidValidationError {}
+
+// Fields a relay sent beyond the standard bid trace, e.g. `optimistic_submission`
+// or `blob_gas_used`. Known extensions have typed accessors; every field is kept
+// verbatim so it survives a round trip.
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(transparent)]
+pub struct BidTraceExtras(BTreeMap<String, Value>);
+
+// Decodes an extra field. Integers too large for a `Value` number are kept as their
+// decimal digits in a string, which `BidTraceExtras::u256` reads like any other.
+fn extra_value(raw: &RawValue) -> Result<Value, serde_json::Error> {
+    let text = raw.get().trim();
+    if text.bytes().all(|b| b.is_ascii_digit()) && text.parse::<u64>().is_err() {
+        return Ok(Value::String(text.to_string()));
+    }
+    serde_json::from_str(text)
+}
+
+impl<'de> Deserialize<'de> for BidTraceExtras {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        BTreeMap::<String, Box<RawValue>>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(name, raw)| Ok((name, extra_value(&raw).map_err(de::Error::custom)?)))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl BidTraceExtras {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+
+    pub fn insert(&mut self, name: &str, value: Value) {
+        self.0.insert(name.to_string(), value);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn as_map(&self) -> &BTreeMap<String, Value> {
+        &self.0
+    }
+
+    // A boolean field, sent either as a JSON boolean or a string
+    pub fn flag(&self, name: &str) -> bool {
+        match self.get(name) {
+            Some(Value::Bool(flag)) => *flag,
+            Some(Value::String(flag)) => flag.eq_ignore_ascii_case("true"),
+            _ => false,
+        }
+    }
+
+    // An integer field, sent as a number, decimal string or hex string
+    pub fn u256(&self, name: &str) -> Option<U256> {
+        match self.get(name)? {
+            Value::Number(number) => parse_u256(&number.to_string()),
+            Value::String(s) => parse_u256(s),
+            _ => None,
+        }
+    }
+
+    pub fn optimistic_submission(&self) -> bool {
+        self.flag("optimistic_submission")
+    }
+
+    // Blob gas used by the block, reported by some relays since Deneb
+    pub fn blob_gas_used(&self) -> Option<U256> {
+        self.u256("blob_gas_used")
+    }
+}
+
+impl From<BTreeMap<String, Value>> for BidTraceExtras {
+    fn from(fields: BTreeMap<String, Value>) -> Self {
+        Self(fields)
+    }
+}
+
+// `Value` is not `Hash`, so fields are hashed by their JSON encoding
+impl Hash for BidTraceExtras {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for (name, value) in &self.0 {
+            name.hash(state);
+            value.to_string().hash(state);
+        }
+    }
+}
+
+// A bid trace as relays send it. Integers may be strings, numbers or hex, and a
+// missing `timestamp_ms` is derived from `timestamp`. Fields beyond the standard
+// ones are collected into `extra`.
+struct RelayBidTrace {
+    slot: U256,
+    parent_hash: B256,
+    block_hash: B256,
+    builder_pubkey: BlsPublicKey,
+    proposer_pubkey: BlsPublicKey,
+    proposer_fee_recipient: Address,
+    gas_limit: U256,
+    gas_used: U256,
+    value: U256,
+    block_number: U256,
+    num_tx: U256,
+    timestamp: U256,
+    timestamp_ms: Option<U256>,
+    extra: BidTraceExtras,
+}
+
+fn set_field<T, E: de::Error>(
+    field: &mut Option<T>,
+    name: &'static str,
+    value: T,
+) -> Result<(), E> {
+    if field.is_some() {
+        return Err(E::duplicate_field(name));
+    }
+    *field = Some(value);
+    Ok(())
+}
+
+fn required<T, E: de::Error>(field: Option<T>, name: &'static str) -> Result<T, E> {
+    field.ok_or_else(|| E::missing_field(name))
+}
+
+impl<'de> Deserialize<'de> for RelayBidTrace {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RelayBidTraceVisitor;
+
+        impl<'de> Visitor<'de> for RelayBidTraceVisitor {
+            type Value = RelayBidTrace;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a bid trace object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut slot = None;
+                let mut parent_hash = None;
+                let mut block_hash = None;
+                let mut builder_pubkey = None;
+                let mut proposer_pubkey = None;
+                let mut proposer_fee_recipient = None;
+                let mut gas_limit = None;
+                let mut gas_used = None;
+                let mut value = None;
+                let mut block_number = None;
+                let mut num_tx = None;
+                let mut timestamp = None;
+                let mut timestamp_ms = None;
+                let mut extra = BidTraceExtras::default();
+
+                while let Some(name) = map.next_key::<String>()? {
+                    match name.as_str() {
+                        "slot" => {
+                            set_field(&mut slot, "slot", map.next_value::<TolerantU256>()?.0)?
+                        }
+                        "parent_hash" => {
+                            set_field(&mut parent_hash, "parent_hash", map.next_value()?)?
+                        }
+                        "block_hash" => {
+                            set_field(&mut block_hash, "block_hash", map.next_value()?)?
+                        }
+                        "builder_pubkey" => {
+                            set_field(&mut builder_pubkey, "builder_pubkey", map.next_value()?)?
+                        }
+                        "proposer_pubkey" => {
+                            set_field(&mut proposer_pubkey, "proposer_pubkey", map.next_value()?)?
+                        }
+                        "proposer_fee_recipient" => set_field(
+                            &mut proposer_fee_recipient,
+                            "proposer_fee_recipient",
+                            map.next_value()?,
+                        )?,
+                        "gas_limit" => set_field(
+                            &mut gas_limit,
+                            "gas_limit",
+                            map.next_value::<TolerantU256>()?.0,
+                        )?,
+                        "gas_used" => set_field(
+                            &mut gas_used,
+                            "gas_used",
+                            map.next_value::<TolerantU256>()?.0,
+                        )?,
+                        "value" => {
+                            set_field(&mut value, "value", map.next_value::<TolerantU256>()?.0)?
+                        }
+                        "block_number" => set_field(
+                            &mut block_number,
+                            "block_number",
+                            map.next_value::<TolerantU256>()?.0,
+                        )?,
+                        "num_tx" => {
+                            set_field(&mut num_tx, "num_tx", map.next_value::<TolerantU256>()?.0)?
+                        }
+                        "timestamp" => set_field(
+                            &mut timestamp,
+                            "timestamp",
+                            map.next_value::<TolerantU256>()?.0,
+                        )?,
+                        "timestamp_ms" => set_field(
+                            &mut timestamp_ms,
+                            "timestamp_ms",
+                            map.next_value::<Option<TolerantU256>>()?
+                                .map(|value| value.0),
+                        )?,
+                        _ => {
+                            let raw = map.next_value::<Box<RawValue>>()?;
+                            let value = extra_value(&raw).map_err(de::Error::custom)?;
+                            extra.insert(&name, value);
+                        }
+                    }
+                }
+
+                Ok(RelayBidTrace {
+                    slot: required(slot, "slot")?,
+                    parent_hash: required(parent_hash, "parent_hash")?,
+                    block_hash: required(block_hash, "block_hash")?,
+                    builder_pubkey: required(builder_pubkey, "builder_pubkey")?,
+                    proposer_pubkey: required(proposer_pubkey, "proposer_pubkey")?,
+                    proposer_fee_recipient: required(
+                        proposer_fee_recipient,
+                        "proposer_fee_recipient",
+                    )?,
+                    gas_limit: required(gas_limit, "gas_limit")?,
+                    gas_used: required(gas_used, "gas_used")?,
+                    value: required(value, "value")?,
+                    block_number: required(block_number, "block_number")?,
+                    num_tx: required(num_tx, "num_tx")?,
+                    timestamp: required(timestamp, "timestamp")?,
+                    timestamp_ms: timestamp_ms.flatten(),
+                    extra,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(RelayBidTraceVisitor)
+    }
+}
+
+impl From<RelayBidTrace> for BidTrace {
+    fn from(bid: RelayBidTrace) -> Self {
+        BidTrace {
+            slot: bid.slot,
+            parent_hash: bid.parent_hash,
+            block_hash: bid.block_hash,
+            builder_pubkey: bid.builder_pubkey,
+            proposer_pubkey: bid.proposer_pubkey,
+            proposer_fee_recipient: bid.proposer_fee_recipient,
+            gas_limit: bid.gas_limit,
+            gas_used: bid.gas_used,
+            value: bid.value,
+            block_number: bid.block_number,
+            num_tx: bid.num_tx,
+            timestamp: bid.timestamp,
+            timestamp_ms: bid
+                .timestamp_ms
+                .unwrap_or_else(|| bid.timestamp.saturating_mul(U256::from(1000))),
+            extra: bid.extra,
+        }
+    }
+}
+
+// Define the BidTrace struct. Equality and hashing cover every field; use `BidKey`
+// to identify the same block across relays.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
+#[serde(from = "RelayBidTrace")]
 pub struct BidTrace {
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(serialize_with = "serialize_u256_as_string")]
     pub slot: U256,
-    pub parent_hash: String,
-    pub block_hash: String,
-    pub builder_pubkey: String,
-    pub proposer_pubkey: String,
+    pub parent_hash: B256,
+    pub block_hash: B256,
+    pub builder_pubkey: BlsPublicKey,
+    pub proposer_pubkey: BlsPublicKey,
     pub proposer_fee_recipient: Address,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(serialize_with = "serialize_u256_as_string")]
     pub gas_limit: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(serialize_with = "serialize_u256_as_string")]
     pub gas_used: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(serialize_with = "serialize_u256_as_string")]
     pub value: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(serialize_with = "serialize_u256_as_string")]
     pub block_number: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(serialize_with = "serialize_u256_as_string")]
     pub num_tx: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(serialize_with = "serialize_u256_as_string")]
     pub timestamp: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(serialize_with = "serialize_u256_as_string")]
     pub timestamp_ms: U256,
-    // Add support for additional information in BidTrace responses
-    pub additional_info: Option<String>,
+    // Fields beyond the standard ones, serialized inline
+    #[serde(flatten)]
+    pub extra: BidTraceExtras,
 }
 
-// Define the BidResponse struct
+// Define the BidResponse struct. `details` holds the kind-specific fields the relay
+// reported with each bid; `malformed` the entries that could not be decoded.
 #[derive(Debug, Clone)]
 pub struct BidResponse {
     pub relay_url: String,
+    pub relay_kind: RelayKind,
     pub bid_traces: Vec<BidTrace>,
+    pub details: HashMap<BidKey, BidDetails>,
+    pub malformed: Vec<MalformedBid>,
+}
+
+// An entry of a relay response that is not a valid bid trace, kept as reported
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct MalformedBid {
+    pub entry: Value,
+    pub error: String,
+}
+
+impl BidResponse {
+    // True when the relay answered successfully but reported no bids
+    pub fn is_empty(&self) -> bool {
+        self.bid_traces.is_empty()
+    }
+
+    // Kind-specific fields reported with `bid`; standard when there are none
+    pub fn details_for(&self, bid: &BidTrace) -> BidDetails {
+        self.details.get(&bid.key()).cloned().unwrap_or_default()
+    }
+}
+
+// A builder's standing on an optimistic relay, as mev-boost-relay's internal
+// `GET /internal/v1/builder/{pubkey}` reports it. Relays turn `is_optimistic` off
+// when they demote a builder after an invalid optimistic block.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct BuilderStatus {
+    pub builder_pubkey: BlsPublicKey,
+    pub is_optimistic: bool,
+    // Collateral the builder posted for optimistic submissions, in wei
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub collateral: U256,
+}
+
+fn serialize_optional_u256_as_string<S>(
+    value: &Option<U256>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match value {
+        Some(value) => serializer.serialize_some(&value.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+// Reads back `serialize_optional_u256_as_string`. Unlike `deserialize_u256` it also
+// works inside internally tagged enums such as `BidDetails`.
+fn deserialize_optional_u256_string<'de, D>(deserializer: D) -> Result<Option<U256>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| {
+            parse_u256(&s)
+                .ok_or_else(|| de::Error::invalid_value(Unexpected::Str(&s), &"an integer string"))
+        })
+        .transpose()
+}
+
+// Fields optimistic relays report with a bid. The ultrasound relay sends
+// `optimistic_submission` on each builder_blocks_received entry; the builder's
+// demotion and collateral come from its builder status and are `None` unless the
+// relay's builder status endpoint is enabled and answered.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct OptimisticBidInfo {
+    // The block was made available before the relay finished simulating it
+    pub optimistic_submission: bool,
+    // The builder's blocks are simulated in full because it lost, or never had,
+    // optimistic status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub demoted: Option<bool>,
+    // Collateral the builder posted for optimistic submissions, in wei
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_optional_u256_as_string",
+        deserialize_with = "deserialize_optional_u256_string"
+    )]
+    pub collateral: Option<U256>,
+}
+
+// Extra fields other relay kinds send too, so they are not XGA metadata
+const SHARED_EXTRA_FIELDS: [&str; 3] =
+    ["optimistic_submission", "blob_gas_used", "excess_blob_gas"];
+
+// Fields XGA relays add to a bid trace, with the same typed accessors as any extras
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+pub struct XgaBidInfo {
+    #[serde(deserialize_with = "deserialize_metadata")]
+    pub metadata: BidTraceExtras,
+}
+
+// `BidTraceExtras` reads numbers from their raw JSON text, which internally tagged
+// enums such as `BidDetails` cannot provide. Serialized metadata already holds
+// large integers as strings, so plain values lose nothing here.
+fn deserialize_metadata<'de, D>(deserializer: D) -> Result<BidTraceExtras, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    BTreeMap::<String, Value>::deserialize(deserializer).map(BidTraceExtras::from)
+}
+
+// Relay-kind-specific information a relay reported alongside a bid
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone, Default)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum BidDetails {
+    #[default]
+    Standard,
+    Optimistic(OptimisticBidInfo),
+    Xga(XgaBidInfo),
+}
+
+impl BidDetails {
+    // Reads the fields a relay of `kind` reported beyond the standard bid trace
+    pub fn from_extra_fields(kind: RelayKind, extra: &BidTraceExtras) -> Self {
+        match kind {
+            RelayKind::Standard => BidDetails::Standard,
+            RelayKind::Optimistic => BidDetails::Optimistic(OptimisticBidInfo {
+                optimistic_submission: extra.optimistic_submission(),
+                ..Default::default()
+            }),
+            RelayKind::Xga => BidDetails::Xga(XgaBidInfo {
+                metadata: extra
+                    .as_map()
+                    .iter()
+                    .filter(|(name, _)| !SHARED_EXTRA_FIELDS.contains(&name.as_str()))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect::<BTreeMap<_, _>>()
+                    .into(),
+            }),
+        }
+    }
+
+    // Adds the demotion and collateral of the bid's builder on an optimistic relay
+    pub fn with_builder_status(mut self, status: &BuilderStatus) -> Self {
+        if let BidDetails::Optimistic(info) = &mut self {
+            info.demoted = Some(!status.is_optimistic);
+            info.collateral = Some(status.collateral);
+        }
+        self
+    }
+
+    pub fn kind(&self) -> RelayKind {
+        match self {
+            BidDetails::Standard => RelayKind::Standard,
+            BidDetails::Optimistic(_) => RelayKind::Optimistic,
+            BidDetails::Xga(_) => RelayKind::Xga,
+        }
+    }
+
+    // True when the relay made the bid available without simulating it first
+    pub fn is_optimistic_submission(&self) -> bool {
+        matches!(self, BidDetails::Optimistic(info) if info.optimistic_submission)
+    }
 }
 
 // Implement Display for BidResponse
@@ -82,35 +711,15 @@ impl fmt::Display for BidTrace {
     }
 }
 
-// Implement Hash for BidTrace
-impl Hash for BidTrace {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.value.hash(state);
-        self.builder_pubkey.hash(state);
-    }
-}
-
-// Implement Ord and PartialOrd for BidTrace
-impl Ord for BidTrace {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.value.cmp(&other.value)
-    }
-}
-
-impl PartialOrd for BidTrace {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
 // Add assertions for BidTrace
 impl BidTrace {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         slot: U256,
-        parent_hash: String,
-        block_hash: String,
-        builder_pubkey: String,
-        proposer_pubkey: String,
+        parent_hash: B256,
+        block_hash: B256,
+        builder_pubkey: BlsPublicKey,
+        proposer_pubkey: BlsPublicKey,
         proposer_fee_recipient: Address,
         gas_limit: U256,
         gas_used: U256,
@@ -119,12 +728,9 @@ impl BidTrace {
         num_tx: U256,
         timestamp: U256,
         timestamp_ms: U256,
-        additional_info: Option<String>,
-    ) -> Self {
-        assert!(slot > U256::zero());
-        assert!(gas_limit > U256::zero());
-        assert!(gas_used <= gas_limit);
-        BidTrace {
+        extra: BidTraceExtras,
+    ) -> Result<Self, BidValidationError> {
+        let bid = BidTrace {
             slot,
             parent_hash,
             block_hash,
@@ -138,145 +744,538 @@ impl BidTrace {
             num_tx,
             timestamp,
             timestamp_ms,
-            additional_info,
+            extra,
+        };
+        bid.validate()?;
+        Ok(bid)
+    }
+
+    // Checks the invariants a bid must hold on its own. Deserialized bids are not
+    // checked until they are validated, e.g. when the bid manager ingests them.
+    pub fn validate(&self) -> Result<(), BidValidationError> {
+        if self.slot.is_zero() {
+            return Err(BidValidationError::ZeroSlot);
+        }
+        if self.gas_limit.is_zero() {
+            return Err(BidValidationError::ZeroGasLimit);
+        }
+        if self.gas_used > self.gas_limit {
+            return Err(BidValidationError::GasUsedAboveLimit {
+                gas_used: self.gas_used,
+                gas_limit: self.gas_limit,
+            });
+        }
+        if self.timestamp_ms / U256::from(1000) != self.timestamp {
+            return Err(BidValidationError::TimestampMismatch {
+                timestamp: self.timestamp,
+                timestamp_ms: self.timestamp_ms,
+            });
+        }
+        if self.value > MAX_BID_VALUE {
+            return Err(BidValidationError::ValueTooHigh { value: self.value });
+        }
+        Ok(())
+    }
+
+    // Like `validate`, and also checks that the bid was submitted within one slot of
+    // its slot's start on `clock`
+    pub fn validate_for(&self, clock: &SlotClock) -> Result<(), BidValidationError> {
+        self.validate()?;
+        // Computed in U256 so absurd slot numbers cannot overflow
+        let tolerance = U256::from(clock.slot_duration().as_secs());
+        let slot_start =
+            U256::from(clock.genesis_time()).saturating_add(self.slot.saturating_mul(tolerance));
+        if self.timestamp.saturating_add(tolerance) < slot_start
+            || self.timestamp > slot_start.saturating_add(tolerance)
+        {
+            return Err(BidValidationError::TimestampOutsideSlot {
+                slot: self.slot,
+                timestamp: self.timestamp,
+            });
+        }
+        Ok(())
+    }
+
+    // Identity of this bid across relays
+    pub fn key(&self) -> BidKey {
+        BidKey::from(self)
+    }
+}
+
+// Define the BidKey struct, the identity of a bid across relays
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+pub struct BidKey {
+    pub slot: u64,
+    pub block_hash: B256,
+    pub builder_pubkey: BlsPublicKey,
+}
+
+impl BidKey {
+    pub fn new(slot: u64, block_hash: B256, builder_pubkey: BlsPublicKey) -> Self {
+        Self {
+            slot,
+            block_hash,
+            builder_pubkey,
         }
     }
 }
-// src/types.rs
 
-use alloy_rs::types::{Address, U256};
-use serde::{Deserialize, Deserializer, Serialize};
-use std::{
-    fmt,
-    hash::{Hash, Hasher},
-};
+impl From<&BidTrace> for BidKey {
+    fn from(bid: &BidTrace) -> Self {
+        BidKey::new(
+            bid.slot.saturating_to::<u64>(),
+            bid.block_hash,
+            bid.builder_pubkey,
+        )
+    }
+}
 
-// Define a custom deserialization function for U256 from string
-fn deserialize_u256_from_string<'de, D>(deserializer: D) -> Result<U256, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s = String::deserialize(deserializer)?;
-    U256::from_dec_str(&s).map_err(serde::de::Error::custom)
+// Implement Display for BidKey
+impl fmt::Display for BidKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BidKey {{ slot: {}, block_hash: {}, builder_pubkey: {} }}",
+            self.slot, self.block_hash, self.builder_pubkey
+        )
+    }
 }
 
-// Define the BidTrace struct
+// Define the DeliveredPayload struct, a payload the relay delivered to the proposer
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
-pub struct BidTrace {
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+pub struct DeliveredPayload {
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
     pub slot: U256,
-    pub parent_hash: String,
-    pub block_hash: String,
-    pub builder_pubkey: String,
-    pub proposer_pubkey: String,
+    pub parent_hash: B256,
+    pub block_hash: B256,
+    pub builder_pubkey: BlsPublicKey,
+    pub proposer_pubkey: BlsPublicKey,
     pub proposer_fee_recipient: Address,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
     pub gas_limit: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
     pub gas_used: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
     pub value: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
     pub block_number: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
     pub num_tx: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
-    pub timestamp: U256,
-    #[serde(deserialize_with = "deserialize_u256_from_string")]
-    pub timestamp_ms: U256,
-    // Add support for additional information in BidTrace responses
-    pub additional_info: Option<String>,
 }
 
-// Define the BidResponse struct
+impl DeliveredPayload {
+    // True when this payload is the block described by `bid`
+    pub fn is_delivery_of(&self, bid: &BidTrace) -> bool {
+        self.slot == bid.slot && self.block_hash == bid.block_hash
+    }
+}
+
+// Implement Display for DeliveredPayload
+impl fmt::Display for DeliveredPayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DeliveredPayload {{ slot: {}, block_number: {}, block_hash: {}, builder_pubkey: {}, proposer_pubkey: {}, value: {}, num_tx: {} }}",
+            self.slot, self.block_number, self.block_hash, self.builder_pubkey, self.proposer_pubkey, self.value, self.num_tx
+        )
+    }
+}
+
+// Define the DeliveredPayloadResponse struct, one page of delivered payloads from a relay
 #[derive(Debug, Clone)]
-pub struct BidResponse {
+pub struct DeliveredPayloadResponse {
     pub relay_url: String,
-    pub bid_traces: Vec<BidTrace>,
+    pub payloads: Vec<DeliveredPayload>,
 }
 
-// Implement Display for BidResponse
-impl fmt::Display for BidResponse {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for bid_trace in &self.bid_traces {
-            writeln!(
-                f,
-                "BidTrace {{ relay_url: {}, block_number: {}, builder_pubkey: {}, value: {}, num_tx: {}, timestamp_ms: {} }}",
-                self.relay_url, bid_trace.block_number, bid_trace.builder_pubkey, bid_trace.value, bid_trace.num_tx, bid_trace.timestamp_ms
-            )?;
+impl DeliveredPayloadResponse {
+    // True when the relay answered successfully but reported no payloads
+    pub fn is_empty(&self) -> bool {
+        self.payloads.is_empty()
+    }
+
+    // Cursor for the next (older) page: relays return payloads in descending slot order,
+    // so the next page starts just below the lowest slot of this page.
+    pub fn next_cursor(&self) -> Option<u64> {
+        self.payloads
+            .iter()
+            .map(|payload| payload.slot.saturating_to::<u64>())
+            .min()
+            .and_then(|slot| slot.checked_sub(1))
+    }
+}
+
+// Define the ValidatorRegistration struct, the preferences a proposer registered with a relay
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ValidatorRegistration {
+    pub fee_recipient: Address,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub gas_limit: U256,
+    #[serde(
+        serialize_with = "serialize_u256_as_string",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub timestamp: U256,
+    pub pubkey: BlsPublicKey,
+}
+
+// Define the SignedValidatorRegistration struct as returned by the relay Data API
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct SignedValidatorRegistration {
+    pub message: ValidatorRegistration,
+    pub signature: String,
+}
+
+// A field on which a bid disagrees with the proposer's registration
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RegistrationMismatch {
+    FeeRecipient {
+        registered: Address,
+        bid: Address,
+    },
+    GasLimit {
+        registered: U256,
+        expected: U256,
+        bid: U256,
+    },
+}
+
+// Execution clients move the gas limit by less than 1/1024 of the parent's per block
+const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
+
+// Gas limit a block built on `parent_gas_limit` should carry when targeting `target`:
+// the target itself when within one step, otherwise one full step towards it
+pub fn expected_gas_limit(parent_gas_limit: U256, target: U256) -> U256 {
+    let max_step =
+        (parent_gas_limit / U256::from(GAS_LIMIT_BOUND_DIVISOR)).saturating_sub(U256::from(1));
+    if target > parent_gas_limit {
+        target.min(parent_gas_limit + max_step)
+    } else {
+        target.max(parent_gas_limit - max_step)
+    }
+}
+
+impl ValidatorRegistration {
+    // Compares a bid with this registration and returns every field that differs.
+    // The gas limit only needs to have moved from `parent_gas_limit` towards the
+    // registered one as far as a single block allows. Bids for other proposers are
+    // not comparable and yield no mismatches.
+    pub fn mismatches(&self, bid: &BidTrace, parent_gas_limit: U256) -> Vec<RegistrationMismatch> {
+        let mut mismatches = Vec::new();
+        if self.pubkey != bid.proposer_pubkey {
+            return mismatches;
         }
-        Ok(())
+        if self.fee_recipient != bid.proposer_fee_recipient {
+            mismatches.push(RegistrationMismatch::FeeRecipient {
+                registered: self.fee_recipient,
+                bid: bid.proposer_fee_recipient,
+            });
+        }
+        let expected = expected_gas_limit(parent_gas_limit, self.gas_limit);
+        if bid.gas_limit != expected {
+            mismatches.push(RegistrationMismatch::GasLimit {
+                registered: self.gas_limit,
+                expected,
+                bid: bid.gas_limit,
+            });
+        }
+        mismatches
     }
 }
 
-// Implement Display for BidTrace
-impl fmt::Display for BidTrace {
+// Implement Display for RegistrationMismatch
+impl fmt::Display for RegistrationMismatch {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "BidTrace {{ slot: {}, parent_hash: {}, block_hash: {}, builder_pubkey: {}, proposer_pubkey: {}, proposer_fee_recipient: {}, gas_limit: {}, gas_used: {}, value: {}, block_number: {}, num_tx: {}, timestamp: {} }}",
-            self.slot, self.parent_hash, self.block_hash, self.builder_pubkey, self.proposer_pubkey, self.proposer_fee_recipient, self.gas_limit, self.gas_used, self.value, self.block_number, self.num_tx, self.timestamp
-        )
+        match self {
+            RegistrationMismatch::FeeRecipient { registered, bid } => write!(
+                f,
+                "fee recipient mismatch: registered {:?}, bid {:?}",
+                registered, bid
+            ),
+            RegistrationMismatch::GasLimit {
+                registered,
+                expected,
+                bid,
+            } => write!(
+                f,
+                "gas limit mismatch: registered {}, expected {}, bid {}",
+                registered, expected, bid
+            ),
+        }
     }
 }
 
-// Implement Hash for BidTrace
-impl Hash for BidTrace {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.value.hash(state);
-        self.builder_pubkey.hash(state);
+// Define the RelayBid struct, a bid annotated with every relay that reported it.
+// `relays` maps each relay URL to the time, in milliseconds since the Unix epoch,
+// at which that relay first reported receiving the block. `conflicting_relays` lists
+// relays whose report for the same `BidKey` disagreed with the stored bid.
+// `details` holds the latest kind-specific fields each relay reported.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct RelayBid {
+    pub bid: BidTrace,
+    pub relays: BTreeMap<String, u64>,
+    pub conflicting_relays: BTreeSet<String>,
+    pub details: BTreeMap<String, BidDetails>,
+}
+
+impl RelayBid {
+    pub fn new(relay_url: &str, bid: BidTrace) -> Self {
+        let first_seen_at = bid.timestamp_ms.saturating_to::<u64>();
+        Self {
+            bid,
+            relays: BTreeMap::from([(relay_url.to_string(), first_seen_at)]),
+            conflicting_relays: BTreeSet::new(),
+            details: BTreeMap::from([(relay_url.to_string(), BidDetails::Standard)]),
+        }
+    }
+
+    // Kinds of the relays that reported this bid
+    pub fn relay_kinds(&self) -> BTreeSet<RelayKind> {
+        self.details.values().map(BidDetails::kind).collect()
+    }
+
+    // True when every relay that reported this bid received it as an optimistic
+    // submission, so no relay has simulated the block
+    pub fn is_optimistic_submission(&self) -> bool {
+        !self.details.is_empty()
+            && self
+                .details
+                .values()
+                .all(BidDetails::is_optimistic_submission)
+    }
+
+    pub fn key(&self) -> BidKey {
+        self.bid.key()
+    }
+
+    // Records that `relay_url` reported this bid at `first_seen_at`, keeping the
+    // earliest sighting per relay. Returns true if the relay was not known yet.
+    pub fn record_relay(&mut self, relay_url: &str, first_seen_at: u64) -> bool {
+        match self.relays.get_mut(relay_url) {
+            Some(seen_at) => {
+                *seen_at = (*seen_at).min(first_seen_at);
+                false
+            }
+            None => {
+                self.relays.insert(relay_url.to_string(), first_seen_at);
+                true
+            }
+        }
+    }
+
+    // The relay that received this bid first, with the time it did so
+    pub fn first_relay(&self) -> Option<(&str, u64)> {
+        self.relays
+            .iter()
+            .min_by_key(|(relay_url, seen_at)| (**seen_at, relay_url.as_str()))
+            .map(|(relay_url, seen_at)| (relay_url.as_str(), *seen_at))
+    }
+
+    pub fn seen_by(&self, relay_url: &str) -> bool {
+        self.relays.contains_key(relay_url)
     }
 }
 
-// Implement Ord and PartialOrd for BidTrace
-impl Ord for BidTrace {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.value.cmp(&other.value)
+// Implement Display for RelayBid
+impl fmt::Display for RelayBid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let relays: Vec<&str> = self.relays.keys().map(String::as_str).collect();
+        write!(f, "{} seen by [{}]", self.bid, relays.join(", "))
     }
 }
 
-impl PartialOrd for BidTrace {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+// Define the BidEvent enum, a change in the auction for a slot. Every newly seen bid
+// produces exactly one of `NewBid` or `NewTopBid`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BidEvent {
+    // A bid that does not beat the slot's current top bid
+    NewBid {
+        relay: String,
+        bid: BidTrace,
+    },
+    // A bid that became the slot's top bid. `delta` is the increase over `previous`,
+    // or the full value for the first bid of a slot.
+    NewTopBid {
+        relay: String,
+        bid: BidTrace,
+        previous: Option<Box<BidTrace>>,
+        #[serde(serialize_with = "serialize_u256_as_string")]
+        delta: U256,
+    },
+    // A known bid was reported by another relay; `relay_count` includes that relay
+    BidSeenOnAdditionalRelay {
+        relay: String,
+        bid: BidTrace,
+        relay_count: usize,
+    },
+    // Polling for the slot ended; `winner` is the top bid at that time
+    SlotClosed {
+        slot: u64,
+        winner: Option<RelayBid>,
+    },
+    // A relay reported a bid that failed validation; it was quarantined
+    BidRejected {
+        relay: String,
+        bid: BidTrace,
+        error: String,
+    },
+    // A relay reported an entry that is not a bid trace; it was quarantined
+    BidMalformed {
+        relay: String,
+        entry: Value,
+        error: String,
+    },
+    // A relay could not be queried
+    RelayError {
+        relay: String,
+        slot: Option<u64>,
+        error: String,
+    },
+    // Polling for the slot is about to start. Slots announced by the beacon node
+    // also carry the proposer and the parent block hash it builds on.
+    SlotWatched {
+        slot: u64,
+        proposer_index: Option<u64>,
+        parent_hash: Option<B256>,
+    },
+    // The beacon node reported a new head block
+    NewHead {
+        slot: u64,
+    },
+    // A beacon node event could not be decoded and was skipped
+    BeaconEventSkipped {
+        beacon_event: String,
+        error: String,
+    },
+}
+
+impl BidEvent {
+    // The bid this event is about, if any
+    pub fn bid(&self) -> Option<&BidTrace> {
+        match self {
+            BidEvent::NewBid { bid, .. }
+            | BidEvent::NewTopBid { bid, .. }
+            | BidEvent::BidSeenOnAdditionalRelay { bid, .. }
+            | BidEvent::BidRejected { bid, .. } => Some(bid),
+            BidEvent::SlotClosed { winner, .. } => winner.as_ref().map(|winner| &winner.bid),
+            BidEvent::BidMalformed { .. }
+            | BidEvent::RelayError { .. }
+            | BidEvent::SlotWatched { .. }
+            | BidEvent::NewHead { .. }
+            | BidEvent::BeaconEventSkipped { .. } => None,
+        }
+    }
+
+    // The relay whose report triggered this event, if any
+    pub fn relay(&self) -> Option<&str> {
+        match self {
+            BidEvent::NewBid { relay, .. }
+            | BidEvent::NewTopBid { relay, .. }
+            | BidEvent::BidSeenOnAdditionalRelay { relay, .. }
+            | BidEvent::BidRejected { relay, .. }
+            | BidEvent::BidMalformed { relay, .. }
+            | BidEvent::RelayError { relay, .. } => Some(relay),
+            BidEvent::SlotClosed { .. }
+            | BidEvent::SlotWatched { .. }
+            | BidEvent::NewHead { .. }
+            | BidEvent::BeaconEventSkipped { .. } => None,
+        }
+    }
+
+    // Whether this event reports the watcher's progress rather than the auction
+    pub fn is_status(&self) -> bool {
+        matches!(
+            self,
+            BidEvent::SlotWatched { .. }
+                | BidEvent::NewHead { .. }
+                | BidEvent::BeaconEventSkipped { .. }
+        )
+    }
+
+    pub fn slot(&self) -> Option<u64> {
+        match self {
+            BidEvent::SlotClosed { slot, .. }
+            | BidEvent::SlotWatched { slot, .. }
+            | BidEvent::NewHead { slot } => Some(*slot),
+            BidEvent::BeaconEventSkipped { .. } => None,
+            BidEvent::RelayError { slot, .. } => *slot,
+            _ => self.bid().map(|bid| bid.slot.saturating_to::<u64>()),
+        }
     }
 }
 
-// Add assertions for BidTrace
-impl BidTrace {
-    pub fn new(
-        slot: U256,
-        parent_hash: String,
-        block_hash: String,
-        builder_pubkey: String,
-        proposer_pubkey: String,
-        proposer_fee_recipient: Address,
-        gas_limit: U256,
-        gas_used: U256,
-        value: U256,
-        block_number: U256,
-        num_tx: U256,
-        timestamp: U256,
-        timestamp_ms: U256,
-        additional_info: Option<String>,
-    ) -> Self {
-        assert!(slot > U256::zero());
-        assert!(gas_limit > U256::zero());
-        assert!(gas_used <= gas_limit);
-        BidTrace {
-            slot,
-            parent_hash,
-            block_hash,
-            builder_pubkey,
-            proposer_pubkey,
-            proposer_fee_recipient,
-            gas_limit,
-            gas_used,
-            value,
-            block_number,
-            num_tx,
-            timestamp,
-            timestamp_ms,
-            additional_info,
+// Implement Display for BidEvent
+impl fmt::Display for BidEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BidEvent::NewBid { relay, bid } => write!(f, "New Bid from {}: {}", relay, bid),
+            BidEvent::NewTopBid {
+                relay, bid, delta, ..
+            } => write!(f, "New Highest Bid from {} (+{}): {}", relay, delta, bid),
+            BidEvent::BidSeenOnAdditionalRelay {
+                relay,
+                bid,
+                relay_count,
+            } => write!(f, "Bid seen on {} ({} relays): {}", relay, relay_count, bid),
+            BidEvent::SlotClosed {
+                slot,
+                winner: Some(winner),
+            } => write!(f, "Slot {} closed, winner: {}", slot, winner),
+            BidEvent::SlotClosed { slot, winner: None } => {
+                write!(f, "Slot {} closed without bids", slot)
+            }
+            BidEvent::BidRejected { relay, bid, error } => {
+                write!(f, "Rejected bid from {} ({}): {}", relay, error, bid)
+            }
+            BidEvent::BidMalformed {
+                relay,
+                entry,
+                error,
+            } => {
+                write!(f, "Malformed bid from {} ({}): {}", relay, error, entry)
+            }
+            BidEvent::RelayError { relay, error, .. } => {
+                write!(f, "Relay {} failed: {}", relay, error)
+            }
+            BidEvent::SlotWatched {
+                slot,
+                proposer_index: Some(proposer_index),
+                parent_hash: Some(parent_hash),
+            } => write!(
+                f,
+                "Watching slot: {} (proposer {}, parent {})",
+                slot, proposer_index, parent_hash
+            ),
+            BidEvent::SlotWatched { slot, .. } => write!(f, "Watching slot: {}", slot),
+            BidEvent::NewHead { slot } => write!(f, "New head: slot {}", slot),
+            BidEvent::BeaconEventSkipped {
+                beacon_event,
+                error,
+            } => {
+                write!(f, "Skipped {} event: {}", beacon_event, error)
+            }
         }
     }
 }
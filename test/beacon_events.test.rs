@@ -0,0 +1,230 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use alloy_primitives::B256;
+    use block_bid_watcher::beacon_events::{BeaconEvent, BeaconEventError, BeaconEventSource};
+    use block_bid_watcher::bid_manager::BidFilter;
+    use block_bid_watcher::relay_clients::RelayClients;
+    use block_bid_watcher::slot_clock::{PollSchedule, SlotClock, SlotWindow};
+    use block_bid_watcher::types::BidEvent;
+    use futures::StreamExt;
+    use tokio::time;
+
+    use crate::common::{bid, bid_traces_json, hash, serve_reply, Reply};
+
+    const HEAD_EVENT: &str = "event: head\ndata: {\"slot\":\"10\",\"block\":\"0x9a2f\",\"state\":\"0x600e\",\"epoch_transition\":false}\n\n";
+
+    const PAYLOAD_ATTRIBUTES_EVENT: &str = "event: payload_attributes\ndata: {\"version\":\"deneb\",\"data\":{\"proposer_index\":\"123\",\"proposal_slot\":\"2\",\"parent_block_number\":\"9\",\"parent_block_root\":\"0x9a2f\",\"parent_block_hash\":\"0x1111111111111111111111111111111111111111111111111111111111111111\",\"payload_attributes\":{\"timestamp\":\"1715000000\",\"prev_randao\":\"0xabcd\",\"suggested_fee_recipient\":\"0x388c818ca8b9251b393131c08a736a67ccb19297\"}}}\n\n";
+
+    // Serves an event stream, writing each chunk separately, then closes it
+    async fn serve_events(status: &'static str, chunks: Vec<&'static str>) -> String {
+        serve_reply(Reply::events(status, chunks)).await.0
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_decodes_head_and_payload_attributes() {
+        let url = serve_events(
+            "200 OK",
+            vec![
+                HEAD_EVENT,
+                "event: finalized_checkpoint\ndata: {}\n\n",
+                PAYLOAD_ATTRIBUTES_EVENT,
+            ],
+        )
+        .await;
+        let source = BeaconEventSource::new(url).unwrap();
+
+        let events: Vec<BeaconEvent> = source
+            .subscribe()
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+
+        assert_eq!(events.len(), 2);
+        let BeaconEvent::Head(head) = &events[0] else {
+            panic!("expected a head event");
+        };
+        assert_eq!(head.slot, 10);
+        let BeaconEvent::PayloadAttributes(attributes) = &events[1] else {
+            panic!("expected a payload attributes event");
+        };
+        assert_eq!(attributes.data.proposal_slot, 2);
+        assert_eq!(attributes.data.proposer_index, 123);
+        assert_eq!(attributes.data.parent_block_hash, B256::repeat_byte(0x11));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_reassembles_events_split_across_writes() {
+        let (first, second) = PAYLOAD_ATTRIBUTES_EVENT.split_at(60);
+        let url = serve_events("200 OK", vec![first, second]).await;
+        let source = BeaconEventSource::new(url).unwrap();
+
+        let events: Vec<_> = source.subscribe().await.unwrap().collect().await;
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], Ok(BeaconEvent::PayloadAttributes(_))));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_keeps_characters_and_crlf_split_across_writes() {
+        // "é" is split between its two bytes, and a CRLF between CR and LF
+        let chunks: Vec<&'static [u8]> = vec![
+            b"event: head\r\ndata: {\"slot\":\"10\",\"block\":\"0x9a2f\",\"state\":\"caf\xc3",
+            b"\xa9\"}\r",
+            b"\n\r\n",
+        ];
+        let (url, _) = serve_reply(Reply::events("200 OK", chunks)).await;
+        let source = BeaconEventSource::new(url).unwrap();
+
+        let events: Vec<_> = source.subscribe().await.unwrap().collect().await;
+
+        assert!(matches!(
+            &events[..],
+            [Ok(BeaconEvent::Head(head))] if head.state == "café"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_ends_a_stalled_stream() {
+        let (url, _) =
+            serve_reply(Reply::events("200 OK", [HEAD_EVENT]).with_linger(Duration::from_secs(5)))
+                .await;
+        let source = BeaconEventSource::new(url)
+            .unwrap()
+            .with_idle_timeout(Duration::from_millis(200));
+
+        let events: Vec<_> = time::timeout(
+            Duration::from_secs(2),
+            source.subscribe().await.unwrap().collect::<Vec<_>>(),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            &events[..],
+            [Ok(BeaconEvent::Head(_)), Err(BeaconEventError::Idle(_))]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_reports_malformed_events() {
+        let url = serve_events("200 OK", vec!["event: head\ndata: {\"slot\":10}\n\n"]).await;
+        let source = BeaconEventSource::new(url).unwrap();
+
+        let events: Vec<_> = source.subscribe().await.unwrap().collect().await;
+
+        assert!(matches!(
+            &events[..],
+            [Err(BeaconEventError::Decode { event, .. })] if event == "head"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_returns_status_errors() {
+        let url = serve_events("503 Service Unavailable", vec!["syncing"]).await;
+        let source = BeaconEventSource::new(url).unwrap();
+
+        let result = source.subscribe().await;
+
+        assert!(matches!(
+            result,
+            Err(BeaconEventError::Status { status, .. }) if status.as_u16() == 503
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_follow_beacon_events_keeps_bids_on_the_announced_parent() {
+        let beacon_url = serve_events("200 OK", vec![PAYLOAD_ATTRIBUTES_EVENT]).await;
+        let on_parent = bid(2, 45000000000000000, "0x22");
+        let mut off_parent = bid(2, 90000000000000000, "0x33");
+        off_parent.parent_hash = B256::repeat_byte(0x44);
+        let (relay_url, _) =
+            serve_reply(Reply::json(bid_traces_json(&[on_parent, off_parent]))).await;
+        let relay_clients = RelayClients::new(vec![relay_url]);
+
+        // Slot 2 starts within two seconds, so its window is already open
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let clock = SlotClock::new(now, 1);
+        let window = SlotWindow {
+            lead: Duration::from_secs(3),
+            deadline: Duration::ZERO,
+        };
+
+        relay_clients
+            .follow_beacon_events(
+                &BeaconEventSource::new(beacon_url).unwrap(),
+                &clock,
+                &window,
+                &PollSchedule::fixed(Duration::from_millis(200)),
+            )
+            .await
+            .unwrap();
+
+        let bids = relay_clients.bid_manager.get_bids_for_slot(2).await;
+        assert_eq!(bids.len(), 1);
+        assert_eq!(bids[0].block_hash, hash("0x22"));
+    }
+
+    #[tokio::test]
+    async fn test_follow_beacon_events_skips_malformed_events() {
+        let beacon_url = serve_events(
+            "200 OK",
+            vec![
+                "event: head\ndata: {\"slot\":10}\n\n",
+                PAYLOAD_ATTRIBUTES_EVENT,
+            ],
+        )
+        .await;
+        let (relay_url, _) = serve_reply(Reply::json(bid_traces_json(&[bid(
+            2,
+            45000000000000000,
+            "0x22",
+        )])))
+        .await;
+        let relay_clients = RelayClients::new(vec![relay_url]);
+        let mut events = relay_clients.bid_manager.subscribe(BidFilter::new());
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let window = SlotWindow {
+            lead: Duration::from_secs(3),
+            deadline: Duration::ZERO,
+        };
+        relay_clients
+            .follow_beacon_events(
+                &BeaconEventSource::new(beacon_url).unwrap(),
+                &SlotClock::new(now, 1),
+                &window,
+                &PollSchedule::fixed(Duration::from_millis(200)),
+            )
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            events.recv().await,
+            Some(BidEvent::BeaconEventSkipped { beacon_event, .. }) if beacon_event == "head"
+        ));
+        assert_eq!(
+            events.recv().await,
+            Some(BidEvent::SlotWatched {
+                slot: 2,
+                proposer_index: Some(123),
+                parent_hash: Some(B256::repeat_byte(0x11)),
+            })
+        );
+        assert_eq!(
+            relay_clients.bid_manager.get_bids_for_slot(2).await.len(),
+            1
+        );
+    }
+}
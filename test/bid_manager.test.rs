@@ -0,0 +1,524 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{B256, U256};
+    use block_bid_watcher::{
+        bid_manager::{BidFilter, BidManager, MergePolicy, MAX_SLOTS_AHEAD, SUBSCRIPTION_CAPACITY},
+        relay_config::RelayKind,
+        slot_clock::SlotClock,
+        types::{
+            BidDetails, BidEvent, BidKey, BidResponse, BidTrace, BidValidationError, BlsPublicKey,
+            OptimisticBidInfo,
+        },
+    };
+
+    use tokio_stream::StreamExt;
+
+    use crate::common::{bid, hash, BUILDER};
+
+    const RELAY_A: &str = "https://relay-a.example";
+    const RELAY_B: &str = "https://relay-b.example";
+
+    #[tokio::test]
+    async fn test_bids_are_partitioned_by_slot() {
+        let bid_manager = BidManager::new();
+
+        bid_manager
+            .add_bids(
+                RELAY_A,
+                vec![bid(100, 5, "0xa"), bid(101, 1, "0xb"), bid(100, 3, "0xc")],
+            )
+            .await;
+
+        assert_eq!(bid_manager.slots().await, vec![100, 101]);
+        assert_eq!(
+            bid_manager
+                .get_highest_bid_for_slot(100)
+                .await
+                .unwrap()
+                .block_hash,
+            hash("0xa")
+        );
+        assert_eq!(
+            bid_manager
+                .get_highest_bid_for_slot(101)
+                .await
+                .unwrap()
+                .block_hash,
+            hash("0xb")
+        );
+        assert_eq!(bid_manager.get_bids_for_slot(100).await.len(), 2);
+        assert!(bid_manager.get_highest_bid_for_slot(102).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_old_slots_are_pruned() {
+        let bid_manager = BidManager::with_retention(2);
+
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(100, 1, "0xa")])
+            .await;
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(101, 1, "0xb")])
+            .await;
+        assert_eq!(bid_manager.slots().await, vec![100, 101]);
+
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(102, 1, "0xc")])
+            .await;
+        assert_eq!(bid_manager.slots().await, vec![101, 102]);
+
+        // Late bids for a pruned slot are dropped
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(100, 9, "0xd")])
+            .await;
+        assert_eq!(bid_manager.slots().await, vec![101, 102]);
+        assert!(bid_manager.get_highest_bid_for_slot(100).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_far_future_slot_does_not_prune_retained_slots() {
+        let bid_manager = BidManager::with_retention(2);
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(100, 1, "0xa"), bid(101, 1, "0xb")])
+            .await;
+
+        let bogus = bid(101 + MAX_SLOTS_AHEAD + 1, 9, "0xc");
+        bid_manager.add_bids(RELAY_B, vec![bogus]).await;
+
+        assert_eq!(bid_manager.slots().await, vec![100, 101]);
+        assert!(matches!(
+            bid_manager.quarantined_bids(RELAY_B).await[0].error,
+            BidValidationError::SlotTooFarAhead {
+                reference_slot: 101,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_slot_clock_bounds_the_retention_window() {
+        let clock = SlotClock::mainnet();
+        let current = clock.current_slot().unwrap();
+        let bid_manager = BidManager::with_retention(2).with_slot_clock(clock);
+        let at_slot = |slot: u64, block_hash: &str| {
+            let mut bid = bid(slot, 1, block_hash);
+            bid.timestamp = U256::from(clock.slot_timestamp(slot));
+            bid.timestamp_ms = bid.timestamp * U256::from(1000);
+            bid
+        };
+
+        bid_manager
+            .add_bids(
+                RELAY_A,
+                vec![at_slot(current - 1, "0xa"), at_slot(current, "0xb")],
+            )
+            .await;
+        bid_manager
+            .add_bids(RELAY_B, vec![at_slot(current + 100, "0xc")])
+            .await;
+
+        assert_eq!(bid_manager.slots().await, vec![current - 1, current]);
+        assert!(matches!(
+            bid_manager.quarantined_bids(RELAY_B).await[0].error,
+            BidValidationError::SlotTooFarAhead { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_highest_bid_is_the_maximum() {
+        let bid_manager = BidManager::new();
+
+        bid_manager
+            .add_bids(
+                RELAY_A,
+                vec![bid(100, 3, "0xa"), bid(100, 7, "0xb"), bid(100, 5, "0xc")],
+            )
+            .await;
+
+        assert_eq!(
+            bid_manager.get_highest_bid().await.unwrap().block_hash,
+            hash("0xb")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_top_of_the_bid_book() {
+        let bid_manager = BidManager::new();
+        let mut other_builder = bid(100, 6, "0xd");
+        other_builder.builder_pubkey = BlsPublicKey::new([0xcc; 48]);
+
+        bid_manager
+            .add_bids(
+                RELAY_A,
+                vec![
+                    bid(100, 3, "0xa"),
+                    bid(100, 7, "0xb"),
+                    bid(100, 5, "0xc"),
+                    other_builder,
+                    bid(100, 5, "0xe"),
+                ],
+            )
+            .await;
+
+        let hashes = |bids: Vec<BidTrace>| -> Vec<B256> {
+            bids.into_iter().map(|bid| bid.block_hash).collect()
+        };
+        assert_eq!(
+            hashes(bid_manager.top_k(100, 3).await),
+            ["0xb", "0xd", "0xc"].map(hash)
+        );
+        assert_eq!(
+            hashes(bid_manager.bids_above(100, U256::from(5)).await),
+            ["0xb", "0xd"].map(hash)
+        );
+        assert_eq!(
+            hashes(
+                bid_manager
+                    .bids_by_builder(100, &BlsPublicKey::new([0xcc; 48]))
+                    .await
+            ),
+            ["0xd"].map(hash)
+        );
+        assert_eq!(
+            hashes(bid_manager.get_bids_for_slot(100).await),
+            ["0xb", "0xd", "0xc", "0xe", "0xa"].map(hash)
+        );
+        assert!(bid_manager.top_k(101, 3).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_same_block_from_several_relays_is_one_bid() {
+        let bid_manager = BidManager::new();
+        let mut top_bids = bid_manager.subscribe_to_top_bids();
+        let mut seen_by_b = bid(100, 5, "0xA");
+        seen_by_b.timestamp = U256::from(1699999999);
+        seen_by_b.timestamp_ms = U256::from(1699999999000u64);
+
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(100, 5, "0xa")])
+            .await;
+        bid_manager.add_bids(RELAY_B, vec![seen_by_b]).await;
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(100, 5, "0xa")])
+            .await;
+
+        assert_eq!(bid_manager.get_bids_for_slot(100).await.len(), 1);
+        let relay_bid = bid_manager
+            .get_relay_bid(&BidKey::new(100, hash("0xa"), BUILDER))
+            .await
+            .unwrap();
+        assert!(relay_bid.seen_by(RELAY_A) && relay_bid.seen_by(RELAY_B));
+        assert_eq!(relay_bid.first_relay(), Some((RELAY_B, 1699999999000)));
+
+        assert!(top_bids.try_recv().is_some());
+        assert!(top_bids.try_recv().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bid_identity_includes_block_hash() {
+        let bid_manager = BidManager::new();
+
+        // Same builder and value, different blocks
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(100, 5, "0xa"), bid(100, 5, "0xb")])
+            .await;
+
+        assert_eq!(bid_manager.get_bids_for_slot(100).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_merge_policy_for_conflicting_reports() {
+        let key = BidKey::new(100, hash("0xa"), BUILDER);
+        let mut conflicting = bid(100, 9, "0xa");
+        conflicting.num_tx = U256::from(101);
+
+        let keep_first = BidManager::new();
+        keep_first.add_bids(RELAY_A, vec![bid(100, 5, "0xa")]).await;
+        keep_first
+            .add_bids(RELAY_B, vec![conflicting.clone()])
+            .await;
+        let record = keep_first.get_relay_bid(&key).await.unwrap();
+        assert_eq!(record.bid.value, U256::from(5));
+        assert!(record.conflicting_relays.contains(RELAY_B));
+        assert!(record.seen_by(RELAY_B));
+
+        let keep_highest = BidManager::new().with_merge_policy(MergePolicy::KeepHighestValue);
+        keep_highest
+            .add_bids(RELAY_A, vec![bid(100, 5, "0xa"), bid(100, 7, "0xb")])
+            .await;
+        keep_highest.add_bids(RELAY_B, vec![conflicting]).await;
+        assert_eq!(keep_highest.get_bids_for_slot(100).await.len(), 2);
+        assert_eq!(
+            keep_highest
+                .get_highest_bid_for_slot(100)
+                .await
+                .unwrap()
+                .value,
+            U256::from(9)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keep_latest_does_not_flip_between_relays() {
+        let bid_manager = BidManager::new().with_merge_policy(MergePolicy::KeepLatest);
+        let mut top_bids = bid_manager.subscribe_to_top_bids();
+        let key = BidKey::new(100, hash("0xa"), BUILDER);
+        let mut higher = bid(100, 9, "0xa");
+        higher.num_tx = U256::from(101);
+
+        // Both relays keep reporting their own view of the block every poll
+        for _ in 0..3 {
+            bid_manager
+                .add_bids(RELAY_A, vec![bid(100, 5, "0xa")])
+                .await;
+            bid_manager.add_bids(RELAY_B, vec![higher.clone()]).await;
+        }
+        assert_eq!(
+            bid_manager.get_relay_bid(&key).await.unwrap().bid.value,
+            U256::from(9)
+        );
+
+        // A relay that updates its own report is taken as the latest
+        let mut updated = bid(100, 5, "0xa");
+        updated.num_tx = U256::from(102);
+        bid_manager.add_bids(RELAY_A, vec![updated]).await;
+        assert_eq!(
+            bid_manager.get_relay_bid(&key).await.unwrap().bid.num_tx,
+            U256::from(102)
+        );
+
+        let mut values = Vec::new();
+        while let Some(event) = top_bids.try_recv() {
+            values.push(event.bid().unwrap().value);
+        }
+        assert_eq!(values, [U256::from(5), U256::from(9)]);
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_lags_without_blocking_ingestion() {
+        let bid_manager = BidManager::new();
+        let mut new_bids = bid_manager.subscribe_to_all_new_bids();
+        let dropped = bid_manager.subscribe_to_top_bids();
+        assert_eq!(bid_manager.subscriber_count(), 2);
+        drop(dropped);
+        assert_eq!(bid_manager.subscriber_count(), 1);
+
+        let total = SUBSCRIPTION_CAPACITY as u64 + 10;
+        let bids = (0..total)
+            .map(|i| bid(100, i, &format!("0x{:x}", i)))
+            .collect();
+        bid_manager.add_bids(RELAY_A, bids).await;
+
+        assert_eq!(
+            new_bids.recv().await.unwrap().bid().unwrap().block_hash,
+            hash("0xa")
+        );
+        assert_eq!(new_bids.missed(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_lagging_stream_reports_missed_events() {
+        let bid_manager = BidManager::new();
+        let mut stream = bid_manager.stream(BidFilter::new());
+
+        let total = SUBSCRIPTION_CAPACITY as u64 + 10;
+        let bids = (0..total)
+            .map(|i| bid(100, i, &format!("0x{:x}", i)))
+            .collect();
+        bid_manager.add_bids(RELAY_A, bids).await;
+
+        assert_eq!(
+            stream.next().await.unwrap().bid().unwrap().block_hash,
+            hash("0xa")
+        );
+        assert_eq!(stream.missed(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_filtered_stream() {
+        let bid_manager = BidManager::new();
+        let mut top_from_a = bid_manager.stream(
+            BidFilter::new()
+                .slots(100..=101)
+                .relays([RELAY_A])
+                .builders([BUILDER])
+                .min_value(U256::from(2))
+                .new_top_only(),
+        );
+        let mut everything = bid_manager.stream(BidFilter::new());
+
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(100, 1, "0xa"), bid(100, 3, "0xb")])
+            .await;
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(100, 2, "0xc")])
+            .await;
+        bid_manager
+            .add_bids(RELAY_B, vec![bid(101, 5, "0xd")])
+            .await;
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(102, 5, "0xe")])
+            .await;
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(101, 6, "0xf")])
+            .await;
+
+        let event = top_from_a.next().await.unwrap();
+        assert!(matches!(event, BidEvent::NewTopBid { .. }));
+        assert_eq!(event.bid().unwrap().block_hash, hash("0xb"));
+        assert_eq!(
+            top_from_a.next().await.unwrap().bid().unwrap().block_hash,
+            hash("0xf")
+        );
+
+        let hashes: Vec<B256> = (&mut everything)
+            .take(6)
+            .map(|event| event.bid().unwrap().block_hash)
+            .collect()
+            .await;
+        assert_eq!(hashes, ["0xa", "0xb", "0xc", "0xd", "0xe", "0xf"].map(hash));
+    }
+
+    #[tokio::test]
+    async fn test_auction_lifecycle_events() {
+        let bid_manager = BidManager::new();
+        let mut events = bid_manager.subscribe(BidFilter::new().slots(100..=100));
+
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(100, 5, "0xa")])
+            .await;
+        bid_manager
+            .add_bids(RELAY_A, vec![bid(100, 3, "0xb")])
+            .await;
+        bid_manager
+            .add_bids(RELAY_B, vec![bid(100, 8, "0xc")])
+            .await;
+        bid_manager
+            .add_bids(RELAY_B, vec![bid(100, 5, "0xa")])
+            .await;
+        bid_manager.close_slot(100).await;
+
+        assert!(matches!(
+            events.try_recv(),
+            Some(BidEvent::NewTopBid { previous: None, delta, .. }) if delta == U256::from(5)
+        ));
+        assert!(matches!(events.try_recv(), Some(BidEvent::NewBid { .. })));
+        match events.try_recv() {
+            Some(BidEvent::NewTopBid {
+                relay,
+                previous: Some(previous),
+                delta,
+                ..
+            }) => {
+                assert_eq!(relay, RELAY_B);
+                assert_eq!(previous.block_hash, hash("0xa"));
+                assert_eq!(delta, U256::from(3));
+            }
+            other => panic!("expected new top bid, got {:?}", other),
+        }
+        assert!(matches!(
+            events.try_recv(),
+            Some(BidEvent::BidSeenOnAdditionalRelay { relay_count: 2, .. })
+        ));
+        match events.try_recv() {
+            Some(BidEvent::SlotClosed {
+                slot: 100,
+                winner: Some(winner),
+            }) => assert_eq!(winner.bid.block_hash, hash("0xc")),
+            other => panic!("expected slot closed, got {:?}", other),
+        }
+        assert!(events.try_recv().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bids_are_tagged_by_relay_kind() {
+        let bid_manager = BidManager::new();
+        let optimistic = bid(5, 100, "0x01");
+        let simulated = bid(5, 90, "0x02");
+        let optimistic_submission = BidDetails::Optimistic(OptimisticBidInfo {
+            optimistic_submission: true,
+            ..Default::default()
+        });
+
+        bid_manager
+            .add_bid_response(BidResponse {
+                relay_url: RELAY_A.to_string(),
+                relay_kind: RelayKind::Optimistic,
+                bid_traces: vec![optimistic.clone(), simulated.clone()],
+                details: [
+                    (optimistic.key(), optimistic_submission.clone()),
+                    (
+                        simulated.key(),
+                        BidDetails::Optimistic(OptimisticBidInfo::default()),
+                    ),
+                ]
+                .into(),
+                malformed: Vec::new(),
+            })
+            .await;
+        bid_manager.add_bids(RELAY_B, vec![simulated.clone()]).await;
+
+        let optimistic_bids = bid_manager
+            .get_relay_bids_by_kind(5, RelayKind::Optimistic)
+            .await;
+        assert_eq!(optimistic_bids.len(), 2);
+        assert!(optimistic_bids[0].is_optimistic_submission());
+        assert!(!optimistic_bids[1].is_optimistic_submission());
+
+        let standard_bids = bid_manager
+            .get_relay_bids_by_kind(5, RelayKind::Standard)
+            .await;
+        assert_eq!(standard_bids.len(), 1);
+        assert_eq!(standard_bids[0].bid, simulated);
+        assert_eq!(
+            standard_bids[0].relay_kinds(),
+            [RelayKind::Standard, RelayKind::Optimistic].into()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_bids_are_quarantined_per_relay() {
+        let bid_manager = BidManager::new().with_slot_clock(SlotClock::new(1000, 12));
+        let mut events = bid_manager.subscribe(BidFilter::new());
+        let mut over_limit = bid(100, 5, "0xa");
+        over_limit.gas_used = U256::from(30000001);
+        // Slot 100 starts at 2200; this bid claims to be from 1700000000
+        let late = bid(100, 6, "0xb");
+        let mut on_time = bid(100, 4, "0xc");
+        on_time.timestamp = U256::from(2195);
+        on_time.timestamp_ms = U256::from(2195000);
+
+        bid_manager
+            .add_bids(RELAY_A, vec![over_limit.clone(), late, on_time.clone()])
+            .await;
+
+        assert_eq!(bid_manager.get_bids_for_slot(100).await, vec![on_time]);
+        let quarantined = bid_manager.quarantined_bids(RELAY_A).await;
+        assert_eq!(quarantined.len(), 2);
+        assert_eq!(quarantined[0].bid, over_limit);
+        assert!(matches!(
+            quarantined[0].error,
+            BidValidationError::GasUsedAboveLimit { .. }
+        ));
+        assert!(matches!(
+            quarantined[1].error,
+            BidValidationError::TimestampOutsideSlot { .. }
+        ));
+        assert!(bid_manager.quarantined_bids(RELAY_B).await.is_empty());
+
+        assert!(matches!(
+            events.try_recv(),
+            Some(BidEvent::BidRejected { relay, .. }) if relay == RELAY_A
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Some(BidEvent::BidRejected { .. })
+        ));
+        assert!(matches!(
+            events.try_recv(),
+            Some(BidEvent::NewTopBid { .. })
+        ));
+    }
+}
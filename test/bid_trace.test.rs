@@ -1,25 +1,133 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use ethers::types::Address;
+    use alloy_primitives::{Address, B256, U256};
+    use block_bid_watcher::slot_clock::SlotClock;
+    use block_bid_watcher::types::{
+        BidDetails, BidTrace, BidTraceExtras, BidValidationError, BlsPublicKey, BlsPublicKeyError,
+        BuilderStatus, OptimisticBidInfo, XgaBidInfo, MAX_BID_VALUE,
+    };
     use std::str::FromStr;
 
+    // A valid bid for slot 10, submitted at 1000 seconds
+    fn valid_bid() -> BidTrace {
+        BidTrace::new(
+            U256::from(10),
+            B256::repeat_byte(0x01),
+            B256::repeat_byte(0x02),
+            BlsPublicKey::new([0xaa; 48]),
+            BlsPublicKey::new([0xbb; 48]),
+            Address::ZERO,
+            U256::from(30000000),
+            U256::from(15000000),
+            U256::from(1000000000000000000u64),
+            U256::from(100),
+            U256::from(50),
+            U256::from(1000),
+            U256::from(1000250),
+            BidTraceExtras::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_rejects_broken_invariants() {
+        let with = |change: fn(&mut BidTrace)| {
+            let mut bid = valid_bid();
+            change(&mut bid);
+            bid.validate()
+        };
+
+        assert_eq!(valid_bid().validate(), Ok(()));
+        assert_eq!(
+            with(|bid| bid.slot = U256::ZERO),
+            Err(BidValidationError::ZeroSlot)
+        );
+        assert_eq!(
+            with(|bid| bid.gas_limit = U256::ZERO),
+            Err(BidValidationError::ZeroGasLimit)
+        );
+        assert!(matches!(
+            with(|bid| bid.gas_used = U256::from(30000001)),
+            Err(BidValidationError::GasUsedAboveLimit { .. })
+        ));
+        assert!(matches!(
+            with(|bid| bid.timestamp_ms = U256::from(1001000)),
+            Err(BidValidationError::TimestampMismatch { .. })
+        ));
+        assert!(matches!(
+            with(|bid| bid.value = MAX_BID_VALUE + U256::from(1)),
+            Err(BidValidationError::ValueTooHigh { .. })
+        ));
+        assert_eq!(with(|bid| bid.value = MAX_BID_VALUE), Ok(()));
+    }
+
+    #[test]
+    fn test_new_returns_an_error_instead_of_panicking() {
+        let bid = valid_bid();
+        let result = BidTrace::new(
+            bid.slot,
+            bid.parent_hash,
+            bid.block_hash,
+            bid.builder_pubkey,
+            bid.proposer_pubkey,
+            bid.proposer_fee_recipient,
+            bid.gas_limit,
+            bid.gas_limit + U256::from(1),
+            bid.value,
+            bid.block_number,
+            bid.num_tx,
+            bid.timestamp,
+            bid.timestamp_ms,
+            bid.extra,
+        );
+
+        assert!(matches!(
+            result,
+            Err(BidValidationError::GasUsedAboveLimit { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_for_checks_timestamp_against_slot() {
+        // Slot 10 starts at 1000 on a clock with genesis at 880 and 12 second slots
+        let clock = SlotClock::new(880, 12);
+        let at = |timestamp: u64| {
+            let mut bid = valid_bid();
+            bid.timestamp = U256::from(timestamp);
+            bid.timestamp_ms = U256::from(timestamp * 1000);
+            bid.validate_for(&clock)
+        };
+
+        assert_eq!(at(988), Ok(()));
+        assert_eq!(at(1012), Ok(()));
+        assert!(matches!(
+            at(987),
+            Err(BidValidationError::TimestampOutsideSlot { .. })
+        ));
+        assert!(matches!(
+            at(1013),
+            Err(BidValidationError::TimestampOutsideSlot { .. })
+        ));
+    }
+
     #[test]
     fn test_bid_trace_serialization() {
         let bid_trace = BidTrace {
             slot: U256::from(12345),
-            parent_hash: "0xparenthash".to_string(),
-            block_hash: "0xblockhash".to_string(),
-            builder_pubkey: "0xbuilderpubkey".to_string(),
-            proposer_pubkey: "0xproposerpubkey".to_string(),
+            parent_hash: B256::repeat_byte(0x01),
+            block_hash: B256::repeat_byte(0x02),
+            builder_pubkey: BlsPublicKey::new([0xaa; 48]),
+            proposer_pubkey: BlsPublicKey::new([0xbb; 48]),
             proposer_fee_recipient: Address::from_str("0x0000000000000000000000000000000000000000")
                 .unwrap(),
             gas_limit: U256::from(1000000),
             gas_used: U256::from(500000),
-            value: U256::from(1000000000000000000),
+            value: U256::from(1000000000000000000u64),
             block_number: U256::from(12345),
             num_tx: U256::from(10),
             timestamp: U256::from(1609459200),
+            timestamp_ms: U256::from(1609459200000u64),
+            extra: BidTraceExtras::default(),
         };
 
         let serialized = serde_json::to_string(&bid_trace).unwrap();
@@ -28,31 +136,29 @@ mod tests {
         assert_eq!(bid_trace, deserialized);
     }
     #[cfg(test)]
-    mod tests {
+    mod serialization {
         use super::*;
-        use ethers::types::Address;
-        use serde_json;
-        use std::str::FromStr;
 
         #[test]
         fn test_bid_trace_serialization_with_timestamp_ms() {
             let bid_trace = BidTrace {
                 slot: U256::from(12345),
-                parent_hash: "0xparenthash".to_string(),
-                block_hash: "0xblockhash".to_string(),
-                builder_pubkey: "0xbuilderpubkey".to_string(),
-                proposer_pubkey: "0xproposerpubkey".to_string(),
+                parent_hash: B256::repeat_byte(0x01),
+                block_hash: B256::repeat_byte(0x02),
+                builder_pubkey: BlsPublicKey::new([0xaa; 48]),
+                proposer_pubkey: BlsPublicKey::new([0xbb; 48]),
                 proposer_fee_recipient: Address::from_str(
                     "0x0000000000000000000000000000000000000000",
                 )
                 .unwrap(),
                 gas_limit: U256::from(1000000),
                 gas_used: U256::from(500000),
-                value: U256::from(1000000000000000000),
+                value: U256::from(1000000000000000000u64),
                 block_number: U256::from(12345),
                 num_tx: U256::from(10),
                 timestamp: U256::from(1609459200),
-                timestamp_ms: U256::from(1609459200123),
+                timestamp_ms: U256::from(1609459200123u64),
+                extra: BidTraceExtras::default(),
             };
 
             let serialized = serde_json::to_string(&bid_trace).unwrap();
@@ -65,10 +171,10 @@ mod tests {
         fn test_bid_trace_default_values() {
             let bid_trace = BidTrace {
                 slot: U256::default(),
-                parent_hash: String::new(),
-                block_hash: String::new(),
-                builder_pubkey: String::new(),
-                proposer_pubkey: String::new(),
+                parent_hash: B256::default(),
+                block_hash: B256::default(),
+                builder_pubkey: BlsPublicKey::default(),
+                proposer_pubkey: BlsPublicKey::default(),
                 proposer_fee_recipient: Address::default(),
                 gas_limit: U256::default(),
                 gas_used: U256::default(),
@@ -77,13 +183,14 @@ mod tests {
                 num_tx: U256::default(),
                 timestamp: U256::default(),
                 timestamp_ms: U256::default(),
+                extra: BidTraceExtras::default(),
             };
 
             assert_eq!(bid_trace.slot, U256::default());
-            assert_eq!(bid_trace.parent_hash, "");
-            assert_eq!(bid_trace.block_hash, "");
-            assert_eq!(bid_trace.builder_pubkey, "");
-            assert_eq!(bid_trace.proposer_pubkey, "");
+            assert_eq!(bid_trace.parent_hash, B256::ZERO);
+            assert_eq!(bid_trace.block_hash, B256::ZERO);
+            assert_eq!(bid_trace.builder_pubkey, BlsPublicKey::new([0; 48]));
+            assert_eq!(bid_trace.proposer_pubkey, BlsPublicKey::new([0; 48]));
             assert_eq!(bid_trace.proposer_fee_recipient, Address::default());
             assert_eq!(bid_trace.gas_limit, U256::default());
             assert_eq!(bid_trace.gas_used, U256::default());
@@ -99,5 +206,212 @@ mod tests {
             let result = Address::from_str("invalid_address");
             assert!(result.is_err());
         }
+
+        #[test]
+        fn test_hashes_and_pubkeys_ignore_hex_case() {
+            let json = |block_hash: &str, builder_pubkey: &str| {
+                format!(
+                    r#"{{"slot":"1","parent_hash":"0x{}","block_hash":"{}","builder_pubkey":"{}","proposer_pubkey":"0x{}","proposer_fee_recipient":"0x0000000000000000000000000000000000000000","gas_limit":"1","gas_used":"0","value":"0","block_number":"1","num_tx":"0","timestamp":"0","timestamp_ms":"0"}}"#,
+                    "11".repeat(32),
+                    block_hash,
+                    builder_pubkey,
+                    "bb".repeat(48)
+                )
+            };
+            let lower: BidTrace = serde_json::from_str(&json(
+                &format!("0x{}", "ab".repeat(32)),
+                &format!("0x{}", "cd".repeat(48)),
+            ))
+            .unwrap();
+            let upper: BidTrace = serde_json::from_str(&json(
+                &format!("0x{}", "AB".repeat(32)),
+                &format!("0x{}", "CD".repeat(48)),
+            ))
+            .unwrap();
+
+            assert_eq!(lower, upper);
+            assert_eq!(lower.key(), upper.key());
+            assert_eq!(
+                serde_json::to_value(&upper).unwrap()["builder_pubkey"],
+                format!("0x{}", "cd".repeat(48))
+            );
+        }
+
+        #[test]
+        fn test_malformed_pubkeys_are_rejected() {
+            assert_eq!(
+                BlsPublicKey::from_str("0xabcd"),
+                Err(BlsPublicKeyError::InvalidLength(2))
+            );
+            assert!(matches!(
+                BlsPublicKey::from_str(&format!("0x{}", "zz".repeat(48))),
+                Err(BlsPublicKeyError::InvalidHex(_))
+            ));
+            assert!(serde_json::from_str::<BlsPublicKey>("\"0xabcd\"").is_err());
+            assert!(serde_json::from_str::<B256>("\"0xabcd\"").is_err());
+        }
+    }
+
+    mod relay_formats {
+        use super::*;
+
+        const ULTRASOUND: &str = include_str!("fixtures/ultrasound_optimistic.json");
+        const FLASHBOTS: &str = include_str!("fixtures/flashbots_standard.json");
+        const NUMERIC: &str = include_str!("fixtures/numeric_fields.json");
+        const HEX: &str = include_str!("fixtures/hex_without_timestamp_ms.json");
+        const LARGE_VALUE: &str = include_str!("fixtures/numeric_large_value.json");
+
+        fn decode(fixture: &str) -> BidTrace {
+            let mut bids: Vec<BidTrace> = serde_json::from_str(fixture).unwrap();
+            assert_eq!(bids.len(), 1);
+            bids.remove(0)
+        }
+
+        #[test]
+        fn test_relay_fixtures_decode_and_validate() {
+            for fixture in [ULTRASOUND, FLASHBOTS, NUMERIC, HEX] {
+                let bid = decode(fixture);
+
+                assert_eq!(bid.slot, U256::from(9000000));
+                assert_eq!(bid.gas_limit, U256::from(30000000));
+                assert_eq!(
+                    bid.builder_pubkey,
+                    decode(ULTRASOUND).builder_pubkey,
+                    "builder pubkey of {}",
+                    fixture
+                );
+                assert_eq!(bid.validate(), Ok(()));
+            }
+        }
+
+        #[test]
+        fn test_numbers_and_hex_strings_are_accepted() {
+            let numeric = decode(NUMERIC);
+            assert_eq!(numeric.num_tx, U256::from(155));
+            assert_eq!(numeric.timestamp_ms, U256::from(1715000000789u64));
+
+            let hex = decode(HEX);
+            assert_eq!(hex.gas_used, U256::from(13000000));
+            assert_eq!(hex.value, U256::from(45000000000000000u64));
+            assert_eq!(hex.block_number, U256::from(19800000));
+            assert_eq!(hex.num_tx, U256::from(150));
+        }
+
+        #[test]
+        fn test_numbers_above_u64_are_decoded_exactly() {
+            // 46 ETH does not fit in a u64 of wei
+            let bid = decode(LARGE_VALUE);
+
+            assert_eq!(
+                bid.value,
+                U256::from_str_radix("46000000000000000000", 10).unwrap()
+            );
+            assert_eq!(bid.validate(), Ok(()));
+
+            let extra: BidTraceExtras =
+                serde_json::from_str(r#"{"payment": 340282366920938463463374607431768211456}"#)
+                    .unwrap();
+            assert_eq!(
+                extra.u256("payment"),
+                Some(U256::from(u128::MAX) + U256::from(1))
+            );
+        }
+
+        #[test]
+        fn test_large_extras_and_repeated_fields() {
+            let with_extra = LARGE_VALUE.replace(
+                r#""num_tx": 155,"#,
+                r#""num_tx": 155, "payment": 46000000000000000001,"#,
+            );
+            let bid = decode(&with_extra);
+            assert_eq!(
+                bid.extra.u256("payment"),
+                Some(U256::from_str_radix("46000000000000000001", 10).unwrap())
+            );
+
+            let repeated =
+                FLASHBOTS.replace(r#""num_tx": "162","#, r#""num_tx": "162", "num_tx": "1","#);
+            assert!(serde_json::from_str::<Vec<BidTrace>>(&repeated).is_err());
+            let missing = FLASHBOTS.replace(r#""num_tx": "162","#, "");
+            assert!(serde_json::from_str::<Vec<BidTrace>>(&missing).is_err());
+        }
+
+        #[test]
+        fn test_missing_timestamp_ms_is_derived_from_timestamp() {
+            let bid = decode(HEX);
+
+            assert_eq!(bid.timestamp, U256::from(1715000000));
+            assert_eq!(bid.timestamp_ms, U256::from(1715000000000u64));
+        }
+
+        #[test]
+        fn test_unknown_fields_are_kept_as_extras() {
+            assert!(decode(FLASHBOTS).extra.is_empty());
+            assert!(decode(ULTRASOUND).extra.optimistic_submission());
+
+            let bid = decode(HEX);
+            assert_eq!(bid.extra.blob_gas_used(), Some(U256::from(0x60000)));
+            assert_eq!(bid.extra.u256("excess_blob_gas"), Some(U256::ZERO));
+
+            // Extras are written back inline, so a re-encoded bid decodes unchanged
+            let json = serde_json::to_value(&bid).unwrap();
+            assert_eq!(json["blob_gas_used"], "0x60000");
+            assert_eq!(json["timestamp_ms"], "1715000000000");
+            assert_eq!(serde_json::from_value::<BidTrace>(json).unwrap(), bid);
+        }
+
+        #[test]
+        fn test_malformed_integers_are_rejected() {
+            for value in [
+                "-1",
+                "1.5",
+                "\"\"",
+                "\"0x\"",
+                "\"1_000\"",
+                "\"12ab\"",
+                "\"0xzz\"",
+                "true",
+            ] {
+                let fixture =
+                    FLASHBOTS.replace(r#""num_tx": "162""#, &format!(r#""num_tx": {}"#, value));
+                assert!(
+                    serde_json::from_str::<Vec<BidTrace>>(&fixture).is_err(),
+                    "num_tx {} should be rejected",
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bid_details_round_trip() {
+        let status = BuilderStatus {
+            builder_pubkey: BlsPublicKey::new([0xaa; 48]),
+            is_optimistic: true,
+            collateral: U256::from_str_radix("46000000000000000001", 10).unwrap(),
+        };
+        let optimistic = BidDetails::Optimistic(OptimisticBidInfo {
+            optimistic_submission: true,
+            ..Default::default()
+        })
+        .with_builder_status(&status);
+        let json = serde_json::to_value(&optimistic).unwrap();
+        assert_eq!(json["kind"], "optimistic");
+        assert_eq!(json["demoted"], false);
+        assert_eq!(json["collateral"], "46000000000000000001");
+        assert_eq!(
+            serde_json::from_value::<BidDetails>(json).unwrap(),
+            optimistic
+        );
+
+        let mut metadata = BidTraceExtras::default();
+        metadata.insert("xga_gas_reserved", "1000000".into());
+        let xga = BidDetails::Xga(XgaBidInfo { metadata });
+        let json = serde_json::to_string(&xga).unwrap();
+        assert_eq!(serde_json::from_str::<BidDetails>(&json).unwrap(), xga);
+
+        // Without a builder status the fields are left out
+        let json = serde_json::to_value(BidDetails::Optimistic(OptimisticBidInfo::default()));
+        assert!(json.unwrap().get("collateral").is_none());
     }
 }
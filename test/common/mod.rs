@@ -0,0 +1,175 @@
+// Bid builders and a stub HTTP server shared by the test crates. Not every crate
+// uses every helper.
+#![allow(dead_code)]
+
+use std::time::Duration;
+
+use alloy_primitives::{Address, B256, U256};
+use block_bid_watcher::types::{BidTrace, BidTraceExtras, BlsPublicKey};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    time,
+};
+
+pub const BUILDER: BlsPublicKey = BlsPublicKey::new([0xaa; 48]);
+pub const PROPOSER: BlsPublicKey = BlsPublicKey::new([0xbb; 48]);
+
+// A short hex block hash such as "0xa", left-padded to 32 bytes
+pub fn hash(short: &str) -> B256 {
+    format!("0x{:0>64}", short.trim_start_matches("0x"))
+        .parse()
+        .unwrap()
+}
+
+pub fn bid(slot: u64, value: u64, block_hash: &str) -> BidTrace {
+    BidTrace::new(
+        U256::from(slot),
+        B256::repeat_byte(0x11),
+        hash(block_hash),
+        BUILDER,
+        PROPOSER,
+        Address::ZERO,
+        U256::from(30000000),
+        U256::from(15000000),
+        U256::from(value),
+        U256::from(slot),
+        U256::from(100),
+        U256::from(1700000000),
+        U256::from(1700000000000u64),
+        BidTraceExtras::default(),
+    )
+    .unwrap()
+}
+
+// A builder_blocks_received response body listing `bids`
+pub fn bid_traces_json(bids: &[BidTrace]) -> String {
+    serde_json::to_string(bids).unwrap()
+}
+
+// A request as the stub server received it. Header lines are lowercased.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub line: String,
+    pub headers: Vec<String>,
+}
+
+// A response for the stub server to send
+#[derive(Debug, Clone)]
+pub struct Reply {
+    status: &'static str,
+    content_type: &'static str,
+    headers: Vec<String>,
+    chunks: Vec<Vec<u8>>,
+    linger: Duration,
+}
+
+impl Reply {
+    // A JSON body with `status`
+    pub fn new(status: &'static str, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            headers: Vec::new(),
+            chunks: vec![body.into().into_bytes()],
+            linger: Duration::ZERO,
+        }
+    }
+
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::new("200 OK", body)
+    }
+
+    // An event stream without a content length, each chunk written separately
+    pub fn events<C: AsRef<[u8]>>(
+        status: &'static str,
+        chunks: impl IntoIterator<Item = C>,
+    ) -> Self {
+        Self {
+            status,
+            content_type: "text/event-stream",
+            headers: Vec::new(),
+            chunks: chunks
+                .into_iter()
+                .map(|chunk| chunk.as_ref().to_vec())
+                .collect(),
+            linger: Duration::ZERO,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push(format!("{}: {}", name, value));
+        self
+    }
+
+    // Keeps the connection open for `linger` after the last chunk
+    pub fn with_linger(mut self, linger: Duration) -> Self {
+        self.linger = linger;
+        self
+    }
+
+    async fn write_to(&self, socket: &mut TcpStream) {
+        let mut head = format!(
+            "HTTP/1.1 {}\r\ncontent-type: {}\r\nconnection: close\r\n",
+            self.status, self.content_type
+        );
+        if self.content_type != "text/event-stream" {
+            let length: usize = self.chunks.iter().map(Vec::len).sum();
+            head.push_str(&format!("content-length: {}\r\n", length));
+        }
+        for header in &self.headers {
+            head.push_str(&format!("{}\r\n", header));
+        }
+        head.push_str("\r\n");
+
+        // The client may hang up early, e.g. on a timeout; that is not an error here
+        let _ = socket.write_all(head.as_bytes()).await;
+        for chunk in &self.chunks {
+            let _ = socket.write_all(chunk).await;
+            let _ = socket.flush().await;
+            if self.chunks.len() > 1 {
+                time::sleep(Duration::from_millis(20)).await;
+            }
+        }
+        time::sleep(self.linger).await;
+    }
+}
+
+// Answers every connection with `respond` and returns the server URL together with
+// the requests received, in order
+pub async fn serve<F>(respond: F) -> (String, mpsc::UnboundedReceiver<Request>)
+where
+    F: Fn(&Request) -> Reply + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap_or(0);
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let mut lines = request.lines();
+            let request = Request {
+                line: lines.next().unwrap_or_default().to_string(),
+                headers: lines
+                    .take_while(|line| !line.is_empty())
+                    .map(str::to_lowercase)
+                    .collect(),
+            };
+
+            let reply = respond(&request);
+            let _ = tx.send(request);
+            reply.write_to(&mut socket).await;
+        }
+    });
+
+    (url, rx)
+}
+
+// Answers every request with the same reply
+pub async fn serve_reply(reply: Reply) -> (String, mpsc::UnboundedReceiver<Request>) {
+    serve(move |_| reply.clone()).await
+}
@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::Duration};
+
+    use block_bid_watcher::{
+        config::{Config, ConfigError, EventSourceConfig, OutputEvents, OutputFormat, RelayEntry},
+        relay_config::{Endpoint, RelayKind},
+        slot_clock::SlotOffset,
+    };
+
+    // Writes `contents` to a fresh file named `name` in the temp directory
+    async fn write_config(name: &str, contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bid-watcher-config-{}", std::process::id()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let path = dir.join(name);
+        tokio::fs::write(&path, contents).await.unwrap();
+        path
+    }
+
+    fn config_with_relays(urls: &[&str]) -> Config {
+        Config {
+            relays: Some(
+                urls.iter()
+                    .map(|url| RelayEntry::new(url, RelayKind::Standard))
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_example_config_loads() {
+        let config = Config::load("config.example.toml").await.unwrap();
+
+        assert_eq!(config.relay_entries().len(), 6);
+        assert_eq!(config.event_source, EventSourceConfig::SlotClock);
+        assert_eq!(
+            config.polling.slot_window().deadline,
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            config
+                .polling
+                .poll_schedule()
+                .interval_at(SlotOffset::SLOT_START),
+            Duration::from_millis(100)
+        );
+
+        let titan = &config.relay_configs()[5];
+        assert_eq!(titan.kind, RelayKind::Optimistic);
+        assert_eq!(titan.request_timeout, Duration::from_millis(1500));
+        assert!(!titan.is_enabled(Endpoint::ValidatorRegistration));
+    }
+
+    #[tokio::test]
+    async fn test_json_config_loads() {
+        let path = write_config(
+            "config.json",
+            r#"{
+                "network": "holesky",
+                "relays": [{ "url": "https://relay.example", "api_key": "secret" }],
+                "event_source": { "kind": "beacon", "url": "http://localhost:5052" },
+                "output": { "format": "json", "events": "all" }
+            }"#,
+        )
+        .await;
+
+        let config = Config::load(&path).await.unwrap();
+
+        assert_eq!(
+            config.event_source,
+            EventSourceConfig::Beacon {
+                url: "http://localhost:5052".to_string()
+            }
+        );
+        assert_eq!(config.output.format, OutputFormat::Json);
+        assert_eq!(config.output.events, OutputEvents::All);
+        assert_eq!(config.relay_configs()[0].headers["authorization"], "secret");
+    }
+
+    #[tokio::test]
+    async fn test_load_reports_parse_and_format_errors() {
+        let unknown_field = write_config(
+            "unknown.toml",
+            "relays = [{ url = \"https://relay.example\", colour = \"blue\" }]",
+        )
+        .await;
+        let yaml = write_config("config.yaml", "relays: []").await;
+
+        assert!(matches!(
+            Config::load(&unknown_field).await,
+            Err(ConfigError::Parse { .. })
+        ));
+        assert!(matches!(
+            Config::load(&yaml).await,
+            Err(ConfigError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            Config::load("does-not-exist.toml").await,
+            Err(ConfigError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_empty_relay_list_is_rejected() {
+        assert!(matches!(
+            config_with_relays(&[]).validate(),
+            Err(ConfigError::NoRelays)
+        ));
+    }
+
+    #[test]
+    fn test_malformed_relay_urls_are_rejected() {
+        for url in [
+            "",
+            "   ",
+            "relay.example",
+            "ftp://relay.example",
+            "https://",
+        ] {
+            assert!(
+                matches!(
+                    config_with_relays(&[url]).validate(),
+                    Err(ConfigError::InvalidRelayUrl { .. })
+                ),
+                "{:?} should be rejected",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_duplicate_relays_are_rejected() {
+        let config = config_with_relays(&["https://Relay.example/", "https://relay.example"]);
+
+        match config.validate() {
+            Err(ConfigError::DuplicateRelay(url)) => assert_eq!(url, "https://relay.example"),
+            other => panic!("expected duplicate relay error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_beacon_url_and_values_are_rejected() {
+        let mut config = config_with_relays(&["https://relay.example"]);
+        config.event_source = EventSourceConfig::Beacon {
+            url: "localhost:5052".to_string(),
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidBeaconUrl { .. })
+        ));
+
+        let mut config = config_with_relays(&["https://relay.example"]);
+        config.retention.slots = 0;
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue {
+                field: "retention.slots",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(Config::default().validate().is_ok());
+    }
+}
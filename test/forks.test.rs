@@ -0,0 +1,135 @@
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::U256;
+    use block_bid_watcher::{
+        forks::{BuilderBid, ForkError, ForkName, SignedBuilderBid},
+        network::{Fork, NetworkSpec},
+        types::BidTrace,
+    };
+    use serde_json::Value;
+
+    const DENEB: &str = include_str!("fixtures/get_header_deneb.json");
+    const ELECTRA: &str = include_str!("fixtures/get_header_electra.json");
+    // Reports the block of the Deneb fixture
+    const BID_TRACE: &str = include_str!("fixtures/ultrasound_optimistic.json");
+
+    const DENEB_SLOT: u64 = 9000000;
+    const ELECTRA_SLOT: u64 = 364032 * 32;
+    const FULU_SLOT: u64 = 411392 * 32;
+
+    #[test]
+    fn test_fork_is_selected_from_slot_and_network() {
+        let mainnet = NetworkSpec::mainnet();
+
+        assert_eq!(mainnet.fork_name_at_slot(0), None);
+        assert_eq!(mainnet.fork_name_at_slot(144896 * 32), None);
+        assert_eq!(
+            mainnet.fork_name_at_slot(194048 * 32),
+            Some(ForkName::Capella)
+        );
+        assert_eq!(mainnet.fork_name_at_slot(DENEB_SLOT), Some(ForkName::Deneb));
+        assert_eq!(
+            mainnet.fork_name_at_slot(ELECTRA_SLOT),
+            Some(ForkName::Electra)
+        );
+
+        // A fork without builder bid types falls back to the latest supported one
+        assert_eq!(mainnet.fork_at_slot(FULU_SLOT).unwrap().name, "fulu");
+        assert_eq!(
+            mainnet.fork_name_at_slot(FULU_SLOT),
+            Some(ForkName::Electra)
+        );
+        let mut devnet = NetworkSpec::holesky();
+        devnet.forks.push(Fork {
+            name: "gloas".to_string(),
+            version: "0x08017000".to_string(),
+            epoch: 200000,
+        });
+        assert_eq!(
+            devnet.fork_name_at_slot(200000 * 32),
+            Some(ForkName::Electra)
+        );
+    }
+
+    #[test]
+    fn test_deneb_bid_carries_blob_data() {
+        let signed =
+            SignedBuilderBid::from_get_header(&NetworkSpec::mainnet(), DENEB_SLOT, DENEB).unwrap();
+        let bid = &signed.message;
+
+        assert_eq!(bid.fork(), ForkName::Deneb);
+        assert_eq!(bid.blob_count(), 3);
+        assert_eq!(bid.header().blob_gas_used(), Some(U256::from(393216)));
+        assert_eq!(bid.header().excess_blob_gas(), Some(U256::ZERO));
+        assert!(bid.has_consistent_blob_gas());
+        assert_eq!(bid.execution_requests(), None);
+
+        let bid_trace: Vec<BidTrace> = serde_json::from_str(BID_TRACE).unwrap();
+        assert!(bid.is_bid_for(&bid_trace[0]));
+    }
+
+    #[test]
+    fn test_electra_bid_carries_execution_requests() {
+        let signed =
+            SignedBuilderBid::from_get_header(&NetworkSpec::mainnet(), ELECTRA_SLOT, ELECTRA)
+                .unwrap();
+        let bid = &signed.message;
+
+        assert_eq!(bid.fork(), ForkName::Electra);
+        assert_eq!(bid.blob_count(), 1);
+        assert!(bid.has_consistent_blob_gas());
+        let requests = bid.execution_requests().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests.deposits[0].amount, U256::from(32000000000u64));
+        assert!(requests.consolidations.is_empty());
+    }
+
+    #[test]
+    fn test_fulu_bid_decodes_with_electra_types() {
+        let fulu = ELECTRA.replace(r#""version": "electra""#, r#""version": "fulu""#);
+
+        let signed =
+            SignedBuilderBid::from_get_header(&NetworkSpec::mainnet(), FULU_SLOT, &fulu).unwrap();
+
+        assert_eq!(signed.message.fork(), ForkName::Electra);
+        assert_eq!(signed.message.execution_requests().unwrap().len(), 2);
+        assert!(matches!(
+            SignedBuilderBid::from_get_header(&NetworkSpec::mainnet(), FULU_SLOT, ELECTRA),
+            Err(ForkError::VersionMismatch { expected, .. }) if expected == "fulu"
+        ));
+    }
+
+    #[test]
+    fn test_bid_of_the_wrong_fork_is_rejected() {
+        let mainnet = NetworkSpec::mainnet();
+
+        assert!(matches!(
+            SignedBuilderBid::from_get_header(&mainnet, ELECTRA_SLOT, DENEB),
+            Err(ForkError::VersionMismatch { expected, .. }) if expected == "electra"
+        ));
+        assert!(matches!(
+            SignedBuilderBid::from_get_header(&mainnet, 0, DENEB),
+            Err(ForkError::UnsupportedSlot(0))
+        ));
+
+        // Without a version the schedule decides, and a Deneb bid lacks Electra fields
+        let unversioned = DENEB.replace(r#""version": "deneb","#, "");
+        assert!(matches!(
+            SignedBuilderBid::from_get_header(&mainnet, ELECTRA_SLOT, &unversioned),
+            Err(ForkError::Decode {
+                fork: ForkName::Electra,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_builder_bid_serializes_to_its_fork_format() {
+        let response: Value = serde_json::from_str(ELECTRA).unwrap();
+        let message = response["data"]["message"].clone();
+
+        let bid = BuilderBid::from_value(ForkName::Electra, message.clone()).unwrap();
+
+        assert_eq!(serde_json::to_value(&bid).unwrap(), message);
+    }
+}
@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use block_bid_watcher::{
+        config::{Config, ConfigError},
+        network::{Network, NetworkSpec},
+        relay_clients::RelayClients,
+    };
+
+    #[test]
+    fn test_presets_drive_slot_clock_and_forks() {
+        let mainnet = Network::Mainnet.spec();
+        let holesky: Network = "Holesky".parse().unwrap();
+
+        assert_eq!(
+            mainnet
+                .slot_clock()
+                .slot_at(UNIX_EPOCH + Duration::from_secs(1714824023)),
+            Some(9000000)
+        );
+        assert_eq!(holesky.slot_clock(), NetworkSpec::holesky().slot_clock());
+        assert_eq!(mainnet.fork_at_slot(9000000).unwrap().name, "deneb");
+        assert_eq!(mainnet.fork_version_at_slot(9000000), "0x04000000");
+        assert_eq!(mainnet.fork_version_at_slot(0), "0x00000000");
+        assert_eq!(mainnet.fork_at_slot(364032 * 32).unwrap().name, "electra");
+        assert!("goerli".parse::<Network>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_default_relays_follow_network() {
+        let config = Config {
+            network: Network::Sepolia,
+            ..Default::default()
+        };
+
+        let relays = config.relay_configs();
+
+        assert_eq!(relays.len(), 1);
+        assert_eq!(relays[0].url, "https://boost-relay-sepolia.flashbots.net");
+        assert!(config.validate().is_ok());
+
+        let relay_clients = RelayClients::for_network(&NetworkSpec::holesky()).unwrap();
+        assert_eq!(relay_clients.relay_urls().await.len(), 3);
+    }
+
+    #[test]
+    fn test_custom_network_from_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [network]
+            name = "devnet"
+            genesis_time = 1000
+            seconds_per_slot = 6
+            genesis_fork_version = "0x10000000"
+            forks = [{ name = "deneb", version = "0x40000000", epoch = 2 }]
+            default_relays = [{ url = "http://localhost:9062" }]
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.validate().is_ok());
+        let spec = config.network.spec();
+        assert_eq!(config.network.to_string(), "devnet");
+        assert_eq!(
+            spec.slot_clock()
+                .slot_at(UNIX_EPOCH + Duration::from_secs(1012)),
+            Some(2)
+        );
+        assert_eq!(spec.fork_version_at_slot(63), "0x10000000");
+        assert_eq!(spec.fork_version_at_slot(64), "0x40000000");
+        assert_eq!(config.relay_configs()[0].url, "http://localhost:9062");
+    }
+
+    #[test]
+    fn test_custom_network_without_relays_is_rejected() {
+        let config: Config = toml::from_str(
+            r#"
+            [network]
+            name = "devnet"
+            genesis_time = 1000
+            genesis_fork_version = "0x10000000"
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(config.validate(), Err(ConfigError::NoRelays)));
+    }
+
+    #[test]
+    fn test_malformed_fork_versions_are_rejected() {
+        let config: Config = toml::from_str(
+            r#"
+            [network]
+            name = "devnet"
+            genesis_time = 1000
+            genesis_fork_version = "0x1000"
+            default_relays = [{ url = "http://localhost:9062" }]
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue {
+                field: "network.forks",
+                ..
+            })
+        ));
+    }
+}
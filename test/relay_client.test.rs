@@ -0,0 +1,490 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{B256, U256};
+    use block_bid_watcher::bid_manager::{BidFilter, BidManager};
+    use block_bid_watcher::relay_client::{
+        BidTraceQuery, DeliveredPayloadQuery, RelayClient, RelayError,
+    };
+    use block_bid_watcher::relay_config::{Endpoint, RelayConfig, RelayKind};
+    use block_bid_watcher::types::{
+        BidDetails, BidEvent, BidTrace, BlsPublicKey, RegistrationMismatch,
+    };
+    use std::time::{Duration, SystemTime};
+    use tokio::{net::TcpListener, sync::mpsc};
+
+    use crate::common::{serve, serve_reply, Reply, Request};
+
+    const BID_TRACES_JSON: &str = include_str!("fixtures/ultrasound_optimistic.json");
+
+    const DELIVERED_PAYLOADS_JSON: &str = r#"[{
+        "slot": "9000001",
+        "parent_hash": "0x1111111111111111111111111111111111111111111111111111111111111111",
+        "block_hash": "0x3333333333333333333333333333333333333333333333333333333333333333",
+        "builder_pubkey": "0xaa1488eae4b06a1fff840a2b6db167afc520758dc2c8af0dfb57037954df3431b747e2f900fe8805f05d635e9a29717b",
+        "proposer_pubkey": "0x8e5ae8a07dd0f7a3fd5c3ae4b8a0e7e4a8a6f8a0e7e4a8a6f8a0e7e4a8a6f8a0e7e4a8a6f8a0e7e4a8a6f8a0e7e4a8a6",
+        "proposer_fee_recipient": "0x388c818ca8b9251b393131c08a736a67ccb19297",
+        "gas_limit": "30000000",
+        "gas_used": "12000000",
+        "value": "45000000000000000",
+        "block_number": "19800001",
+        "num_tx": "150"
+    }, {
+        "slot": "9000000",
+        "parent_hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+        "block_hash": "0x1111111111111111111111111111111111111111111111111111111111111111",
+        "builder_pubkey": "0xaa1488eae4b06a1fff840a2b6db167afc520758dc2c8af0dfb57037954df3431b747e2f900fe8805f05d635e9a29717b",
+        "proposer_pubkey": "0x8e5ae8a07dd0f7a3fd5c3ae4b8a0e7e4a8a6f8a0e7e4a8a6f8a0e7e4a8a6f8a0e7e4a8a6f8a0e7e4a8a6f8a0e7e4a8a6",
+        "proposer_fee_recipient": "0x388c818ca8b9251b393131c08a736a67ccb19297",
+        "gas_limit": "30000000",
+        "gas_used": "15000000",
+        "value": "51000000000000000",
+        "block_number": "19800000",
+        "num_tx": "170"
+    }]"#;
+
+    // Serves `body` with `status` and hands back the requests received
+    async fn serve_once(
+        status: &'static str,
+        body: impl Into<String>,
+    ) -> (String, mpsc::UnboundedReceiver<Request>) {
+        serve_reply(Reply::new(status, body)).await
+    }
+
+    fn first_bid() -> BidTrace {
+        serde_json::from_str::<Vec<BidTrace>>(BID_TRACES_JSON)
+            .unwrap()
+            .remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_get_builder_bids_decodes_bid_traces() {
+        let (url, mut requests) = serve_once("200 OK", BID_TRACES_JSON).await;
+        let client = RelayClient::new(url.clone());
+
+        let response = client.get_builder_bids(19800000).await.unwrap();
+
+        assert_eq!(response.relay_url, url);
+        assert_eq!(response.bid_traces.len(), 1);
+        assert_eq!(response.bid_traces[0].num_tx, U256::from(150));
+        assert_eq!(
+            requests.recv().await.unwrap().line,
+            "GET /relay/v1/data/bidtraces/builder_blocks_received?block_number=19800000 HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_are_sent() {
+        let (url, mut requests) = serve_once("200 OK", "[]").await;
+        let client = RelayClient::new(url);
+        let query = BidTraceQuery {
+            slot: Some(9000000),
+            block_hash: Some(B256::repeat_byte(0xab)),
+            builder_pubkey: Some(BlsPublicKey::new([0xcd; 48])),
+            ..Default::default()
+        };
+
+        let response = client.get_builder_blocks_received(&query).await.unwrap();
+
+        assert!(response.is_empty());
+        assert_eq!(
+            requests.recv().await.unwrap().line,
+            format!(
+                "GET /relay/v1/data/bidtraces/builder_blocks_received?slot=9000000&block_hash=0x{}&builder_pubkey=0x{} HTTP/1.1",
+                "ab".repeat(32),
+                "cd".repeat(48)
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_delivered_payloads_paginates() {
+        let (url, mut requests) = serve_once("200 OK", DELIVERED_PAYLOADS_JSON).await;
+        let client = RelayClient::new(url);
+
+        let response = client
+            .get_delivered_payloads(&DeliveredPayloadQuery::page(Some(9000001), 2))
+            .await
+            .unwrap();
+
+        assert_eq!(response.payloads.len(), 2);
+        assert_eq!(response.next_cursor(), Some(8999999));
+        assert_eq!(
+            requests.recv().await.unwrap().line,
+            "GET /relay/v1/data/bidtraces/proposer_payload_delivered?cursor=9000001&limit=2 HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delivered_payload_matches_bid() {
+        let (url, _) = serve_once("200 OK", DELIVERED_PAYLOADS_JSON).await;
+        let client = RelayClient::new(url);
+        let bid = first_bid();
+
+        let payload = client
+            .get_delivered_payload_for_slot(9000000)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(payload.num_tx, U256::from(170));
+        assert!(!payload.is_delivery_of(&bid));
+    }
+
+    #[tokio::test]
+    async fn test_validator_registration_flags_mismatched_bid() {
+        let registration_json = r#"{
+            "message": {
+                "fee_recipient": "0x388c818ca8b9251b393131c08a736a67ccb19297",
+                "gas_limit": "36000000",
+                "timestamp": "1714999000",
+                "pubkey": "0x8E5AE8A07DD0F7A3FD5C3AE4B8A0E7E4A8A6F8A0E7E4A8A6F8A0E7E4A8A6F8A0E7E4A8A6F8A0E7E4A8A6F8A0E7E4A8A6"
+            },
+            "signature": "0xabcdef"
+        }"#;
+        let (url, mut requests) = serve_once("200 OK", registration_json).await;
+        let client = RelayClient::new(url);
+        let bid = first_bid();
+
+        let registration = client
+            .get_validator_registration(&bid.proposer_pubkey)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(requests
+            .recv()
+            .await
+            .unwrap()
+            .line
+            .starts_with("GET /relay/v1/data/validator_registration?pubkey=0x8e5ae8a07dd0f7a3"));
+        // Built on a 30M parent, the bid should have moved one step towards 36M
+        let parent_gas_limit = U256::from(30000000);
+        assert_eq!(
+            registration.message.mismatches(&bid, parent_gas_limit),
+            vec![RegistrationMismatch::GasLimit {
+                registered: U256::from(36000000),
+                expected: U256::from(30029295),
+                bid: U256::from(30000000),
+            }]
+        );
+
+        let mut converging = bid.clone();
+        converging.gas_limit = U256::from(30029295);
+        assert!(registration
+            .message
+            .mismatches(&converging, parent_gas_limit)
+            .is_empty());
+        // A full step from a lower parent lands exactly on the bid's 30M
+        assert!(registration
+            .message
+            .mismatches(&bid, U256::from(29970733))
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_validator_has_no_registration() {
+        let (url, _) = serve_once(
+            "400 Bad Request",
+            r#"{"code":400,"message":"no registration found for validator 0xabc"}"#,
+        )
+        .await;
+        let client = RelayClient::new(url);
+        let pubkey = BlsPublicKey::new([0xab; 48]);
+
+        assert_eq!(
+            client.get_validator_registration(&pubkey).await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_status_is_reported() {
+        let (url, _) = serve_once("429 Too Many Requests", "rate limited").await;
+        let client = RelayClient::new(url);
+
+        match client.get_builder_bids(1).await {
+            Err(RelayError::Status {
+                status,
+                body,
+                retry_after,
+            }) => {
+                assert_eq!(status.as_u16(), 429);
+                assert_eq!(body, "rate limited");
+                assert_eq!(retry_after, None);
+            }
+            other => panic!("expected status error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_reported() {
+        let (url, _) = serve_reply(
+            Reply::new("503 Service Unavailable", "busy").with_header("retry-after", "3"),
+        )
+        .await;
+        let client = RelayClient::new(url);
+
+        let err = client.get_builder_bids(1).await.unwrap_err();
+
+        assert!(err.is_retryable());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(3)));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_date_is_reported() {
+        let retry_at = SystemTime::now() + Duration::from_secs(30);
+        let (url, _) = serve_reply(
+            Reply::new("429 Too Many Requests", "slow down")
+                .with_header("retry-after", &httpdate::fmt_http_date(retry_at)),
+        )
+        .await;
+        let client = RelayClient::new(url);
+
+        let retry_after = client
+            .get_builder_bids(1)
+            .await
+            .unwrap_err()
+            .retry_after()
+            .unwrap();
+
+        // HTTP dates have whole-second precision
+        assert!(retry_after > Duration::from_secs(28) && retry_after <= Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_body_is_decode_error() {
+        let (url, _) = serve_once("200 OK", r#"{"unexpected": true}"#).await;
+        let client = RelayClient::new(url);
+
+        assert!(matches!(
+            client.get_builder_bids(1).await,
+            Err(RelayError::Decode(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_entry_does_not_fail_the_response() {
+        let mut entries: Vec<serde_json::Value> = serde_json::from_str(BID_TRACES_JSON).unwrap();
+        let mut malformed = entries[0].clone();
+        malformed["builder_pubkey"] = "0xnot-a-key".into();
+        entries.push(malformed.clone());
+        let (url, _) = serve_once("200 OK", serde_json::to_string(&entries).unwrap()).await;
+        let client = RelayClient::new(url.clone());
+
+        let response = client.get_builder_bids(19800000).await.unwrap();
+
+        assert_eq!(response.bid_traces, vec![first_bid()]);
+        assert_eq!(response.malformed.len(), 1);
+        assert_eq!(response.malformed[0].entry, malformed);
+
+        let bid_manager = BidManager::new();
+        let mut events = bid_manager.subscribe(BidFilter::new());
+        bid_manager.add_bid_response(response).await;
+
+        assert_eq!(bid_manager.get_bids_for_slot(9000000).await.len(), 1);
+        assert_eq!(bid_manager.malformed_bids(&url).await.len(), 1);
+        assert!(matches!(
+            events.recv().await,
+            Some(BidEvent::BidMalformed { relay, entry, .. }) if relay == url && entry == malformed
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_relay_is_http_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let client = RelayClient::new(url);
+
+        assert!(matches!(
+            client.get_builder_bids(1).await,
+            Err(RelayError::Http(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_configured_headers_are_sent() {
+        let (url, mut requests) = serve_once("200 OK", "[]").await;
+        let config = RelayConfig::new(url)
+            .with_api_key("secret-key")
+            .with_header("X-Client", "bid-watcher");
+        let client = RelayClient::from_config(config).unwrap();
+
+        client.get_builder_bids(1).await.unwrap();
+
+        let headers = requests.recv().await.unwrap().headers;
+        assert!(headers.contains(&"authorization: secret-key".to_string()));
+        assert!(headers.contains(&"x-client: bid-watcher".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_header_is_rejected() {
+        let config = RelayConfig::new("https://relay".to_string()).with_header("bad header", "x");
+
+        assert!(RelayClient::from_config(config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_endpoint_is_not_requested() {
+        let config = RelayConfig::new("http://127.0.0.1:1".to_string())
+            .with_endpoints([Endpoint::BuilderBlocksReceived]);
+        let client = RelayClient::from_config(config).unwrap();
+
+        assert!(matches!(
+            client.get_delivered_payload_for_slot(1).await,
+            Err(RelayError::EndpointDisabled(
+                Endpoint::ProposerPayloadDelivered
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_spaces_requests() {
+        let (url, _) = serve_once("200 OK", "[]").await;
+        let config = RelayConfig::new(url).with_max_requests_per_second(1);
+        let client = RelayClient::from_config(config).unwrap();
+
+        assert_eq!(
+            client.config.min_request_interval(),
+            Some(Duration::from_secs(1))
+        );
+        assert!(client.is_ready());
+        client.get_builder_bids(1).await.unwrap();
+        assert!(!client.is_ready());
+    }
+
+    #[tokio::test]
+    async fn test_optimistic_relay_fields_are_parsed() {
+        let (url, _) = serve_once("200 OK", BID_TRACES_JSON).await;
+        let config = RelayConfig::new(url).with_kind(RelayKind::Optimistic);
+        let client = RelayClient::from_config(config).unwrap();
+
+        let response = client.get_builder_bids(19800000).await.unwrap();
+
+        assert_eq!(response.relay_kind, RelayKind::Optimistic);
+        let details = response.details_for(&response.bid_traces[0]);
+        assert!(details.is_optimistic_submission());
+        assert_eq!(details.kind(), RelayKind::Optimistic);
+    }
+
+    #[tokio::test]
+    async fn test_optimistic_relay_fields_come_from_builder_status() {
+        let (url, mut requests) = serve(|request| {
+            if request.line.contains("/internal/v1/builder/") {
+                Reply::json(format!(
+                    r#"{{"builder_pubkey": "{}", "is_optimistic": false, "collateral": "1000000000000000000"}}"#,
+                    first_bid().builder_pubkey
+                ))
+            } else {
+                Reply::json(BID_TRACES_JSON)
+            }
+        })
+        .await;
+        let config = RelayConfig::new(url)
+            .with_kind(RelayKind::Optimistic)
+            .with_endpoints(
+                Endpoint::DATA_API
+                    .into_iter()
+                    .chain([Endpoint::BuilderStatus]),
+            );
+        let client = RelayClient::from_config(config).unwrap();
+
+        client.get_builder_bids(19800000).await.unwrap();
+        let response = client.get_builder_bids(19800000).await.unwrap();
+
+        match response.details_for(&response.bid_traces[0]) {
+            BidDetails::Optimistic(info) => {
+                assert!(info.optimistic_submission);
+                assert_eq!(info.demoted, Some(true));
+                assert_eq!(info.collateral, Some(U256::from(1000000000000000000u64)));
+            }
+            other => panic!("expected optimistic details, got {:?}", other),
+        }
+        // The builder's status is reused for the second poll
+        let mut status_requests = 0;
+        while let Ok(request) = requests.try_recv() {
+            if request.line.contains("/internal/v1/builder/") {
+                status_requests += 1;
+            }
+        }
+        assert_eq!(status_requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_builder_status_is_not_requested_unless_enabled() {
+        let (url, mut requests) = serve_once("200 OK", BID_TRACES_JSON).await;
+        let config = RelayConfig::new(url).with_kind(RelayKind::Optimistic);
+        let client = RelayClient::from_config(config).unwrap();
+
+        let response = client.get_builder_bids(19800000).await.unwrap();
+
+        match response.details_for(&response.bid_traces[0]) {
+            BidDetails::Optimistic(info) => {
+                assert_eq!(info.demoted, None);
+                assert_eq!(info.collateral, None);
+            }
+            other => panic!("expected optimistic details, got {:?}", other),
+        }
+        assert!(!requests.recv().await.unwrap().line.contains("/internal/"));
+        assert!(requests.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_builder_has_no_status() {
+        let (url, _) = serve_once(
+            "400 Bad Request",
+            r#"{"code":400,"message":"builder not found"}"#,
+        )
+        .await;
+        let config = RelayConfig::new(url).with_endpoints([Endpoint::BuilderStatus]);
+        let client = RelayClient::from_config(config).unwrap();
+
+        let status = client.get_builder_status(&first_bid().builder_pubkey).await;
+
+        assert!(matches!(status, Ok(None)));
+    }
+
+    #[tokio::test]
+    async fn test_relay_format_variations_are_decoded() {
+        let (url, _) = serve_once(
+            "200 OK",
+            include_str!("fixtures/hex_without_timestamp_ms.json"),
+        )
+        .await;
+        let client = RelayClient::new(url);
+
+        let response = client.get_builder_bids(19800000).await.unwrap();
+
+        let bid = &response.bid_traces[0];
+        assert_eq!(bid.slot, U256::from(9000000));
+        assert_eq!(bid.timestamp_ms, U256::from(1715000000000u64));
+        assert_eq!(bid.extra.blob_gas_used(), Some(U256::from(0x60000)));
+        assert_eq!(response.details_for(bid), BidDetails::Standard);
+    }
+
+    #[tokio::test]
+    async fn test_xga_relay_metadata_is_kept() {
+        let body = BID_TRACES_JSON.replace(
+            r#""optimistic_submission": true"#,
+            r#""xga_gas_reserved": "1000000""#,
+        );
+        let (url, _) = serve_once("200 OK", body).await;
+        let config = RelayConfig::new(url).with_kind(RelayKind::Xga);
+        let client = RelayClient::from_config(config).unwrap();
+
+        let response = client.get_builder_bids(19800000).await.unwrap();
+
+        match response.details_for(&response.bid_traces[0]) {
+            BidDetails::Xga(info) => {
+                assert_eq!(info.metadata.as_map().len(), 1);
+                assert_eq!(
+                    info.metadata.u256("xga_gas_reserved"),
+                    Some(U256::from(1000000))
+                );
+            }
+            other => panic!("expected XGA details, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use block_bid_watcher::relay_client::BidTraceQuery;
+    use block_bid_watcher::relay_client::RelayError;
+    use block_bid_watcher::relay_clients::{RelayClients, RelaySetChange};
+    use block_bid_watcher::relay_config::{Endpoint, RelayConfig, RelayConfigError, RelayKind};
+    use block_bid_watcher::relay_health::RelayStatus;
+    use block_bid_watcher::slot_clock::SlotClock;
+    use block_bid_watcher::types::{BidEvent, BlsPublicKey};
+    use reqwest::StatusCode;
+
+    use crate::common::{bid, bid_traces_json, serve, serve_reply, Reply, Request};
+
+    // Answers bid queries like mev-boost-relay: 400 unless a slot, block or builder
+    // filter is given
+    fn filtered_bids(request: &Request) -> Reply {
+        let filtered = ["slot=", "block_number=", "builder_pubkey="]
+            .iter()
+            .any(|filter| request.line.contains(filter));
+        if filtered {
+            Reply::json("[]")
+        } else {
+            Reply::new(
+                "400 Bad Request",
+                r#"{"code":400,"message":"need a query argument"}"#,
+            )
+        }
+    }
+
+    fn configs(urls: &[&str]) -> Vec<RelayConfig> {
+        urls.iter()
+            .map(|url| RelayConfig::new(url.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_reload_reports_added_removed_and_updated_relays() {
+        let relay_clients =
+            RelayClients::from_configs(configs(&["http://a", "http://b", "http://c"])).unwrap();
+        let unchanged = relay_clients.clients().await[0].clone();
+
+        let change = relay_clients
+            .reload_relays(vec![
+                RelayConfig::new("http://a".to_string()),
+                RelayConfig::new("http://c".to_string()).with_kind(RelayKind::Optimistic),
+                RelayConfig::new("http://d/".to_string()),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            change,
+            RelaySetChange {
+                added: vec!["http://d".to_string()],
+                removed: vec!["http://b".to_string()],
+                updated: vec!["http://c".to_string()],
+            }
+        );
+        assert_eq!(
+            relay_clients.relay_urls().await,
+            vec!["http://a", "http://c", "http://d"]
+        );
+        // Unchanged relays keep their client and its rate limit state
+        assert!(Arc::ptr_eq(&relay_clients.clients().await[0], &unchanged));
+        // Dropping the optimistic kind again is another update
+        assert!(relay_clients
+            .reload_relays(configs(&["http://a", "http://c", "http://d"]))
+            .await
+            .unwrap()
+            .updated
+            .contains(&"http://c".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_reload_keeps_current_relays() {
+        let relay_clients = RelayClients::from_configs(configs(&["http://a"])).unwrap();
+
+        let result = relay_clients
+            .reload_relays(vec![
+                RelayConfig::new("http://b".to_string()),
+                RelayConfig::new("http://c".to_string()).with_header("bad header", "x"),
+            ])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(RelayConfigError::InvalidHeader { .. })
+        ));
+        assert_eq!(relay_clients.relay_urls().await, vec!["http://a"]);
+    }
+
+    #[tokio::test]
+    async fn test_add_and_remove_relay() {
+        let relay_clients = RelayClients::from_configs(configs(&["http://a"])).unwrap();
+        let error = RelayError::Status {
+            status: StatusCode::BAD_GATEWAY,
+            body: String::new(),
+            retry_after: None,
+        };
+        relay_clients
+            .health
+            .record_failure("http://a", &error, None)
+            .await;
+
+        relay_clients
+            .add_relay(RelayConfig::new("http://b".to_string()))
+            .await
+            .unwrap();
+        relay_clients
+            .add_relay(RelayConfig::new("http://a".to_string()).with_kind(RelayKind::Xga))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            relay_clients.relay_urls().await,
+            vec!["http://a", "http://b"]
+        );
+        assert_eq!(relay_clients.clients().await[0].config.kind, RelayKind::Xga);
+        // Reconfigured relays start over with a clean health record
+        assert_eq!(
+            relay_clients.health.get("http://a").await.status(None),
+            RelayStatus::Healthy
+        );
+
+        assert!(relay_clients.remove_relay("http://b/").await);
+        assert!(!relay_clients.remove_relay("http://b").await);
+        assert_eq!(relay_clients.relay_urls().await, vec!["http://a"]);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_adds_are_all_kept() {
+        let relay_clients = Arc::new(RelayClients::from_configs(configs(&["http://a"])).unwrap());
+
+        let adds = ["http://b", "http://c", "http://d", "http://e"].map(|url| {
+            let relay_clients = relay_clients.clone();
+            tokio::spawn(async move {
+                relay_clients
+                    .add_relay(RelayConfig::new(url.to_string()))
+                    .await
+            })
+        });
+        for add in adds {
+            add.await.unwrap().unwrap();
+        }
+
+        let mut urls = relay_clients.relay_urls().await;
+        urls.sort();
+        assert_eq!(
+            urls,
+            vec!["http://a", "http://b", "http://c", "http://d", "http://e"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reload_keeps_bids_and_subscriptions() {
+        let (first_relay, _) = serve_reply(Reply::json(bid_traces_json(&[bid(
+            2,
+            45000000000000000,
+            "0x2",
+        )])))
+        .await;
+        let (second_relay, _) = serve_reply(Reply::json(bid_traces_json(&[bid(
+            2,
+            90000000000000000,
+            "0x3",
+        )])))
+        .await;
+        let relay_clients = RelayClients::new(vec![first_relay.clone()]);
+        let mut receiver = relay_clients.bid_manager.subscribe_to_all_new_bids();
+
+        let fetch = || async {
+            for (_, result) in relay_clients.fetch_bids(&BidTraceQuery::by_slot(2)).await {
+                relay_clients
+                    .bid_manager
+                    .add_bid_response(result.unwrap())
+                    .await;
+            }
+        };
+
+        fetch().await;
+        relay_clients
+            .reload_relays(configs(&[&second_relay]))
+            .await
+            .unwrap();
+        fetch().await;
+
+        let bids = relay_clients.bid_manager.get_bids_for_slot(2).await;
+        assert_eq!(bids.len(), 2);
+        // The subscription made before the reload sees bids from both relay sets
+        let mut relays = Vec::new();
+        while let Ok(Some(event)) =
+            tokio::time::timeout(Duration::from_millis(100), receiver.recv()).await
+        {
+            if let BidEvent::NewBid { relay, .. } | BidEvent::NewTopBid { relay, .. } = event {
+                relays.push(relay);
+            }
+        }
+        assert_eq!(relays, vec![first_relay, second_relay]);
+    }
+
+    #[tokio::test]
+    async fn test_check_relays_probes_bids_with_a_slot_filter() {
+        let (url, _) = serve(filtered_bids).await;
+        let config =
+            RelayConfig::new(url.clone()).with_endpoints([Endpoint::BuilderBlocksReceived]);
+        let relay_clients = RelayClients::from_configs(vec![config]).unwrap();
+
+        let results = relay_clients.check_relays(&SlotClock::mainnet()).await;
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].1.is_ok(), "{:?}", results[0].1);
+        assert_eq!(
+            relay_clients.health.get(&url).await.status(None),
+            RelayStatus::Healthy
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registrations_skip_relays_without_the_endpoint() {
+        let config = RelayConfig::new("http://127.0.0.1:1".to_string())
+            .with_endpoints([Endpoint::BuilderBlocksReceived]);
+        let relay_clients = RelayClients::from_configs(vec![config]).unwrap();
+
+        let registrations = relay_clients
+            .get_validator_registrations(&BlsPublicKey::new([0xbb; 48]))
+            .await;
+
+        assert!(registrations.is_empty());
+    }
+}
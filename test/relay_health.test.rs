@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use block_bid_watcher::relay_client::RelayError;
+    use block_bid_watcher::relay_health::{
+        HealthPolicy, RelayHealth, RelayHealthTracker, RelayStatus,
+    };
+    use reqwest::StatusCode;
+    use tokio::time::Instant;
+
+    fn status_error(status: StatusCode, retry_after: Option<Duration>) -> RelayError {
+        RelayError::Status {
+            status,
+            body: String::new(),
+            retry_after,
+        }
+    }
+
+    fn policy() -> HealthPolicy {
+        HealthPolicy {
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            failure_threshold: 3,
+            pause_slots: 2,
+        }
+    }
+
+    #[test]
+    fn test_backoff_grows_exponentially_up_to_max() {
+        let now = Instant::now();
+        let error = status_error(StatusCode::BAD_GATEWAY, None);
+        let mut health = RelayHealth::default();
+
+        let mut backoffs = Vec::new();
+        for _ in 0..4 {
+            health.record_failure(&error, None, now, &policy());
+            backoffs.push(health.backoff_until.unwrap() - now);
+        }
+
+        assert_eq!(
+            backoffs,
+            [100, 200, 400, 500].map(Duration::from_millis).to_vec()
+        );
+        assert!(!health.is_available(None, now + Duration::from_millis(499)));
+        assert!(health.is_available(None, now + Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_retry_after_extends_backoff() {
+        let now = Instant::now();
+        let mut health = RelayHealth::default();
+
+        health.record_failure(
+            &status_error(StatusCode::TOO_MANY_REQUESTS, Some(Duration::from_secs(5))),
+            None,
+            now,
+            &policy(),
+        );
+
+        assert_eq!(health.backoff_until, Some(now + Duration::from_secs(5)));
+        assert_eq!(health.status(None), RelayStatus::Degraded);
+    }
+
+    #[test]
+    fn test_non_retryable_errors_do_not_throttle() {
+        let now = Instant::now();
+        let mut health = RelayHealth::default();
+
+        health.record_failure(
+            &status_error(StatusCode::BAD_REQUEST, None),
+            Some(10),
+            now,
+            &policy(),
+        );
+
+        assert_eq!(health.total_failures, 1);
+        assert_eq!(health.status(None), RelayStatus::Healthy);
+        assert!(health.is_available(Some(10), now));
+    }
+
+    #[test]
+    fn test_circuit_breaker_pauses_relay_for_slots() {
+        let now = Instant::now();
+        let error = status_error(StatusCode::SERVICE_UNAVAILABLE, None);
+        let mut health = RelayHealth::default();
+
+        for _ in 0..3 {
+            health.record_failure(&error, Some(10), now, &policy());
+        }
+        let later = now + Duration::from_secs(60);
+
+        assert_eq!(
+            health.status(Some(12)),
+            RelayStatus::Paused { through_slot: 12 }
+        );
+        assert!(!health.is_available(Some(12), later));
+        assert!(health.is_available(Some(13), later));
+        assert_eq!(health.status(Some(13)), RelayStatus::Recovering);
+
+        health.record_success();
+        assert_eq!(health.status(Some(13)), RelayStatus::Healthy);
+        assert_eq!(health.total_requests, 4);
+    }
+
+    #[tokio::test]
+    async fn test_tracker_reports_degraded_relays() {
+        let tracker = RelayHealthTracker::new(policy());
+
+        tracker.record_success("https://relay-a").await;
+        tracker
+            .record_failure(
+                "https://relay-b",
+                &status_error(StatusCode::BAD_GATEWAY, None),
+                Some(10),
+            )
+            .await;
+
+        assert!(tracker.is_available("https://relay-a", Some(10)).await);
+        assert!(!tracker.is_available("https://relay-b", Some(10)).await);
+        assert!(tracker.is_available("https://unknown", Some(10)).await);
+        let degraded = tracker.degraded(Some(10)).await;
+        assert_eq!(degraded.len(), 1);
+        assert_eq!(degraded[0].0, "https://relay-b");
+        assert_eq!(degraded[0].1, RelayStatus::Degraded);
+        assert_eq!(tracker.snapshot().await.len(), 2);
+    }
+}
@@ -0,0 +1,113 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::U256;
+    use block_bid_watcher::{
+        bid_manager::{BidFilter, BidManager},
+        replay::{parse_replay, replay, ReplayError},
+        types::BidEvent,
+    };
+
+    use crate::common::bid;
+
+    // A line as `watch --output json` prints it
+    fn event_line(event: &BidEvent) -> String {
+        serde_json::to_string(event).unwrap()
+    }
+
+    #[test]
+    fn test_parse_replay_reads_watch_output() {
+        let contents = [
+            event_line(&BidEvent::NewTopBid {
+                relay: "https://relay-a".to_string(),
+                bid: bid(5, 100, "0xa"),
+                previous: None,
+                delta: U256::from(100),
+            }),
+            String::new(),
+            event_line(&BidEvent::SlotClosed {
+                slot: 5,
+                winner: None,
+            }),
+            event_line(&BidEvent::NewBid {
+                relay: "https://relay-b".to_string(),
+                bid: bid(6, 50, "0xb"),
+            }),
+        ]
+        .join("\n");
+
+        let records = parse_replay(&contents).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].relay, "https://relay-a");
+        assert_eq!(records[0].bid, bid(5, 100, "0xa"));
+        assert_eq!(records[1].bid.slot, U256::from(6));
+    }
+
+    #[test]
+    fn test_parse_replay_reports_line_numbers() {
+        let contents =
+            "Watching slot: 5\n\n{\"relay\": \"https://relay-a\", \"bid\": {\"slot\": \"5\"}}\n";
+
+        assert!(matches!(
+            parse_replay(contents),
+            Err(ReplayError::Parse { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_replay_skips_lines_that_are_not_json() {
+        let contents = [
+            "Watching slot: 123".to_string(),
+            event_line(&BidEvent::NewBid {
+                relay: "https://relay-a".to_string(),
+                bid: bid(123, 50, "0xb"),
+            }),
+            "New head: slot 123".to_string(),
+            "not json {".to_string(),
+        ]
+        .join("\n");
+
+        let records = parse_replay(&contents).unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].bid, bid(123, 50, "0xb"));
+        assert!(parse_replay("Watching slot: 123\n").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replay_closes_slots_in_order() {
+        let bid_manager = BidManager::new();
+        let mut receiver = bid_manager.subscribe(BidFilter::default());
+        let records = parse_replay(
+            &[
+                event_line(&BidEvent::NewBid {
+                    relay: "https://relay-a".to_string(),
+                    bid: bid(5, 100, "0xa"),
+                }),
+                event_line(&BidEvent::NewBid {
+                    relay: "https://relay-a".to_string(),
+                    bid: bid(6, 50, "0xb"),
+                }),
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+
+        replay(&bid_manager, records).await;
+
+        let mut events = Vec::new();
+        while let Some(event) = receiver.try_recv() {
+            events.push(event);
+        }
+        assert!(matches!(events[0], BidEvent::NewTopBid { .. }));
+        assert!(matches!(
+            &events[1],
+            BidEvent::SlotClosed { slot: 5, winner: Some(winner) } if winner.bid.value == U256::from(100)
+        ));
+        assert!(matches!(events[2], BidEvent::NewTopBid { .. }));
+        assert!(matches!(events[3], BidEvent::SlotClosed { slot: 6, .. }));
+        assert_eq!(events.len(), 4);
+    }
+}
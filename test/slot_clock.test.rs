@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use block_bid_watcher::slot_clock::{
+        PollPhase, PollSchedule, SlotClock, SlotOffset, SlotWindow, MAINNET_GENESIS_TIME,
+        MIN_POLL_INTERVAL,
+    };
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_slot_at_and_slot_start() {
+        let clock = SlotClock::mainnet();
+        let slot_start = UNIX_EPOCH + Duration::from_secs(1714824023);
+
+        assert_eq!(clock.slot_at(slot_start), Some(9000000));
+        assert_eq!(
+            clock.slot_at(slot_start + Duration::from_millis(11999)),
+            Some(9000000)
+        );
+        assert_eq!(
+            clock.slot_at(slot_start + Duration::from_secs(12)),
+            Some(9000001)
+        );
+        assert_eq!(clock.slot_start(9000000), slot_start);
+        assert_eq!(clock.slot_timestamp(0), MAINNET_GENESIS_TIME);
+    }
+
+    #[test]
+    fn test_before_genesis_has_no_slot() {
+        let clock = SlotClock::new(1000, 12);
+
+        assert_eq!(clock.slot_at(UNIX_EPOCH + Duration::from_secs(999)), None);
+        assert_eq!(
+            clock.slot_at(UNIX_EPOCH + Duration::from_secs(1000)),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_slot_window() {
+        let clock = SlotClock::new(1000, 12);
+        let window = SlotWindow {
+            lead: Duration::from_secs(4),
+            deadline: Duration::from_secs(2),
+        };
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+
+        assert_eq!(window.start(&clock, 10), at(1116));
+        assert_eq!(window.end(&clock, 10), at(1122));
+        // Slot 10 starts at 1120; its window is still open one second later
+        assert_eq!(window.next_slot(&clock, at(1121)), 10);
+        assert_eq!(window.next_slot(&clock, at(1122)), 11);
+    }
+
+    #[test]
+    fn test_slot_offset_between() {
+        let slot_start = UNIX_EPOCH + Duration::from_secs(1120);
+
+        assert_eq!(
+            SlotOffset::between(slot_start, slot_start - Duration::from_millis(1500)),
+            SlotOffset::before(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            SlotOffset::between(slot_start, slot_start + Duration::from_millis(250)).as_millis(),
+            250
+        );
+        assert!(SlotOffset::before(Duration::from_secs(1)) < SlotOffset::SLOT_START);
+    }
+
+    #[test]
+    fn test_poll_schedule_intervals() {
+        let schedule = PollSchedule::default();
+        let interval_at = |millis: i64| {
+            let offset = if millis < 0 {
+                SlotOffset::before(Duration::from_millis(millis.unsigned_abs()))
+            } else {
+                SlotOffset::after(Duration::from_millis(millis as u64))
+            };
+            schedule.interval_at(offset)
+        };
+
+        // Before the first phase its interval applies
+        assert_eq!(interval_at(-10_000), Duration::from_secs(1));
+        assert_eq!(interval_at(-5_000), Duration::from_secs(1));
+        assert_eq!(interval_at(-2_000), Duration::from_millis(250));
+        assert_eq!(interval_at(-1), Duration::from_millis(250));
+        assert_eq!(interval_at(0), Duration::from_millis(100));
+        assert_eq!(interval_at(900), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_poll_schedule_sorts_and_clamps_phases() {
+        let schedule = PollSchedule::new(vec![
+            PollPhase {
+                from: SlotOffset::SLOT_START,
+                interval: Duration::ZERO,
+            },
+            PollPhase {
+                from: SlotOffset::before(Duration::from_secs(4)),
+                interval: Duration::from_millis(500),
+            },
+        ]);
+
+        assert_eq!(
+            schedule.phases()[0].from,
+            SlotOffset::before(Duration::from_secs(4))
+        );
+        assert_eq!(
+            schedule.interval_at(SlotOffset::after(Duration::from_secs(1))),
+            MIN_POLL_INTERVAL
+        );
+        assert_eq!(
+            PollSchedule::new(Vec::new()).interval_at(SlotOffset::SLOT_START),
+            Duration::from_secs(1)
+        );
+    }
+}